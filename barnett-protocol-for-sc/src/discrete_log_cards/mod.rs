@@ -445,3 +445,79 @@ impl<'a, C:  ProjectiveCurve> BarnettSmartProtocol for DLCards<'a, C> {
         )
     }
 }
+
+/// Verification of a shuffle proof spread over several calls, for decks whose proof is too
+/// expensive to check at once. Every call to `verify_next_stage` runs one stage of the shuffle
+/// argument and keeps the Fiat-Shamir transcript for the next one, so all calls must be given
+/// the same inputs. A verifier that returned an error must be discarded.
+pub struct ShuffleVerifier<C: ProjectiveCurve> {
+    fs_rng: FiatShamirRng<Blake2s>,
+    challenges: Option<shuffle::proof::Challenges<C::ScalarField>>,
+    verified_stages: usize,
+}
+
+impl<C: ProjectiveCurve> ShuffleVerifier<C> {
+    /// The commitments, the product argument and the multi-exponentiation argument.
+    pub const STAGES: usize = 3;
+
+    pub fn new() -> Result<Self, CryptoError> {
+        Ok(Self {
+            fs_rng: FiatShamirRng::<Blake2s>::from_seed(&to_bytes![SHUFFLE_RNG_SEED]?),
+            challenges: None,
+            verified_stages: 0,
+        })
+    }
+
+    pub fn verified_stages(&self) -> usize {
+        self.verified_stages
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.verified_stages == Self::STAGES
+    }
+
+    /// Runs the next stage and returns whether the whole proof is now verified.
+    pub fn verify_next_stage(
+        &mut self,
+        pp: &Parameters<C>,
+        shared_key: &PublicKey<C>,
+        original_deck: &Vec<MaskedCard<C>>,
+        shuffled_deck: &Vec<MaskedCard<C>>,
+        proof: &shuffle::proof::Proof<C::ScalarField, ElGamal<C>, PedersenCommitment<C>>,
+    ) -> Result<bool, CryptoError> {
+        let shuffle_parameters = shuffle::Parameters::new(
+            &pp.enc_parameters,
+            shared_key,
+            &pp.commit_parameters,
+            &pp.generator,
+        );
+        let shuffle_statement = shuffle::Statement::new(original_deck, shuffled_deck, pp.m, pp.n);
+
+        match (self.verified_stages, &self.challenges) {
+            (0, _) => {
+                let challenges = proof.verify_commitments(
+                    &shuffle_parameters,
+                    &shuffle_statement,
+                    &mut self.fs_rng,
+                )?;
+                self.challenges = Some(challenges);
+            }
+            (1, Some(challenges)) => proof.verify_product_argument(
+                &shuffle_parameters,
+                &shuffle_statement,
+                challenges,
+                &mut self.fs_rng,
+            )?,
+            (2, Some(challenges)) => proof.verify_multi_exponentiation(
+                &shuffle_parameters,
+                &shuffle_statement,
+                challenges,
+                &mut self.fs_rng,
+            )?,
+            _ => return Ok(self.is_complete()),
+        }
+
+        self.verified_stages += 1;
+        Ok(self.is_complete())
+    }
+}
@@ -1,3 +1,5 @@
+use sc_poker_io::ProgramMetadata;
+
 fn main() {
-    gear_wasm_builder::build();
+    gear_wasm_builder::build_with_metadata::<ProgramMetadata>();
 }
@@ -0,0 +1,53 @@
+//! Deterministic mapping between playing cards and el-Gamal plaintexts.
+//!
+//! Every card is hashed to a point of the curve with a public domain separator, so the
+//! contract and the clients derive the same plaintexts without trusting anyone's mapping.
+
+use crate::{ClassicPlayingCard, NUM_OF_CARDS};
+use ark_ff::PrimeField;
+use blake2::{Blake2s, Digest};
+use gstd::prelude::*;
+use proof_essentials::homomorphic_encryption::el_gamal::Plaintext;
+use starknet_curve::{Affine, Fq};
+
+/// The curve the cards are encoded on.
+pub type Curve = starknet_curve::Projective;
+
+/// An open card, the same type as `discrete_log_cards::Card<Curve>`.
+pub type Card = Plaintext<Curve>;
+
+/// Domain separator hashed together with every card.
+pub const CARD_ENCODING_DOMAIN: &[u8] = b"sc-poker/card-encoding/v1";
+
+/// Hashes `card` to a curve point with try-and-increment: the first counter for which
+/// `Blake2s(domain || value || suite || counter)` is the x-coordinate of a point wins,
+/// and the smaller of the two y-coordinates is taken.
+pub fn encode_card(card: ClassicPlayingCard) -> Card {
+    for counter in 0..=u8::MAX {
+        let digest = Blake2s::new()
+            .chain(CARD_ENCODING_DOMAIN)
+            .chain([card.value as u8, card.suite as u8, counter])
+            .finalize();
+        let x = Fq::from_le_bytes_mod_order(&digest);
+
+        if let Some(point) = Affine::get_point_from_x(x, false) {
+            return Plaintext(point);
+        }
+    }
+
+    unreachable!("Half of the x-coordinates are on the curve")
+}
+
+/// Encodings of the whole deck in the canonical order of `ClassicPlayingCard::from_index`.
+pub fn encode_deck() -> Vec<Card> {
+    (0..NUM_OF_CARDS)
+        .map(|index| encode_card(ClassicPlayingCard::from_index(index)))
+        .collect()
+}
+
+/// Reverse lookup of a plaintext in a deck produced by `encode_deck`.
+pub fn decode_card(deck: &[Card], plaintext: &Card) -> Option<ClassicPlayingCard> {
+    deck.iter()
+        .position(|card| card == plaintext)
+        .map(ClassicPlayingCard::from_index)
+}
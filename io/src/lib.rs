@@ -0,0 +1,623 @@
+#![no_std]
+
+use barnett_smart_card_protocol_for_sc::error::CardProtocolError;
+use gmeta::{InOut, Metadata};
+use gstd::{prelude::*, ActorId};
+use proof_essentials::error::CryptoError;
+
+pub mod encoding;
+pub mod lobby;
+
+pub struct ProgramMetadata;
+
+impl Metadata for ProgramMetadata {
+    type Init = InOut<InitGame, Result<(), GameError>>;
+    type Handle = InOut<GameMessage, Result<GameEvent, GameError>>;
+    type Others = ();
+    type Reply = ();
+    type Signal = ();
+    type State = ProgramState;
+}
+
+/// Identifier of a table within the program. The table set up by `init` is `0`.
+pub type GameId = u64;
+
+pub const NUM_OF_CARDS: usize = 52;
+
+/// Upper bound on the number of seats: two hole cards per seat and the five
+/// community cards have to fit into a 52-card deck.
+pub const MAX_PLAYERS: u8 = 10;
+
+pub const HOLE_CARDS: usize = 2;
+pub const COMMUNITY_CARDS: usize = 5;
+
+#[derive(Encode, Decode, TypeInfo, Debug, PartialEq, Clone, Copy, Eq)]
+pub enum Suite {
+    Club,
+    Diamond,
+    Heart,
+    Spade,
+}
+
+impl Suite {
+    pub const VALUES: [Self; 4] = [Self::Club, Self::Diamond, Self::Heart, Self::Spade];
+}
+
+#[derive(Encode, Decode, TypeInfo, Debug, PartialEq, PartialOrd, Ord, Clone, Copy, Eq)]
+pub enum Value {
+    Two,
+    Three,
+    Four,
+    Five,
+    Six,
+    Seven,
+    Eight,
+    Nine,
+    Ten,
+    Jack,
+    Queen,
+    King,
+    Ace,
+}
+
+impl Value {
+    pub const VALUES: [Self; 13] = [
+        Self::Two,
+        Self::Three,
+        Self::Four,
+        Self::Five,
+        Self::Six,
+        Self::Seven,
+        Self::Eight,
+        Self::Nine,
+        Self::Ten,
+        Self::Jack,
+        Self::Queen,
+        Self::King,
+        Self::Ace,
+    ];
+}
+
+#[derive(Encode, Decode, TypeInfo, Debug, PartialEq, Clone, Eq, Copy)]
+pub struct ClassicPlayingCard {
+    pub value: Value,
+    pub suite: Suite,
+}
+
+impl ClassicPlayingCard {
+    pub fn new(value: Value, suite: Suite) -> Self {
+        Self { value, suite }
+    }
+
+    /// The card at `index` in the canonical deck order: `Value::VALUES` outer,
+    /// `Suite::VALUES` inner.
+    pub fn from_index(index: usize) -> Self {
+        Self::new(Value::VALUES[index / 4], Suite::VALUES[index % 4])
+    }
+}
+
+#[derive(Encode, Decode, TypeInfo, Debug, PartialEq, PartialOrd, Ord, Clone, Copy, Eq)]
+pub enum HandCategory {
+    HighCard,
+    OnePair,
+    TwoPair,
+    ThreeOfAKind,
+    Straight,
+    Flush,
+    FullHouse,
+    FourOfAKind,
+    StraightFlush,
+    RoyalFlush,
+}
+
+/// Stages of a hand. Each `GameAction` is only accepted in some of them and the table
+/// moves forward as the corresponding protocol step completes.
+#[derive(Encode, Decode, TypeInfo, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// Players join the table with `JoinTable` or leave it with `LeaveTable`, before the
+    /// first hand and again between hands.
+    Registration,
+    /// Registration is closed and the joint key and the initial deck are being computed.
+    KeyAggregation,
+    /// Players shuffle the deck one after another in seat order.
+    Shuffling,
+    /// The deck is shuffled and positions are about to be assigned to the players.
+    Dealing,
+    /// Blinds are posted. Players first exchange the reveal tokens for each other's hole
+    /// cards, then the first betting round is played.
+    PreFlop,
+    /// The street's community cards are opened with reveal tokens, then a betting round
+    /// follows. The table moves to the next street once the betting is closed.
+    Flop,
+    Turn,
+    River,
+    /// The players still in the hand open their hole cards by submitting the reveal tokens
+    /// for them. The hands are ranked and the pots paid once every one of them is open.
+    Showdown,
+    /// Fewer than two players with chips are left and everybody has been paid out.
+    Finished,
+}
+
+#[derive(Encode, Decode, TypeInfo)]
+pub struct InitGame {
+    pub enc_parameters: Vec<u8>,
+    pub commit_parameters: Vec<u8>,
+    pub generator: Vec<u8>,
+    pub max_players: u8,
+    pub small_blind: u128,
+    pub big_blind: u128,
+    /// Limits for the buy-in, the part of the value attached to `JoinTable` which becomes
+    /// the player's stack.
+    pub min_buy_in: u128,
+    pub max_buy_in: u128,
+    /// Collateral attached to `JoinTable` on top of the buy-in. It is returned with the
+    /// stack unless the player is caught sending an invalid proof.
+    pub deposit: u128,
+    /// Blocks a player has to shuffle, submit a reveal token or act on a bet.
+    pub turn_timeout: u32,
+    /// Lobby program to report the phase and the seat count of the table to. Set by the
+    /// lobby when it deploys the table.
+    pub lobby: Option<ActorId>,
+}
+
+/// A serialized reveal token for the card at `card_index` and its proof.
+#[derive(Encode, Decode, TypeInfo)]
+pub struct RevealTokenShare {
+    pub card_index: u8,
+    pub token: Vec<u8>,
+    pub proof: Vec<u8>,
+}
+
+/// Messages handled by the program.
+#[derive(Encode, Decode, TypeInfo)]
+pub enum GameMessage {
+    /// Opens a new table. The reply is `TableCreated` with its id.
+    CreateTable(InitGame),
+    /// `action` at the table `game_id`.
+    Action { game_id: GameId, action: GameAction },
+    /// Removes a `Finished` table whose players have withdrawn everything. Can be sent by
+    /// anybody, the id is not given out again.
+    RetireTable { game_id: GameId },
+}
+
+#[derive(Encode, Decode, TypeInfo)]
+pub enum GameAction {
+    /// Takes a seat. The value attached to the message is the deposit plus the buy-in.
+    JoinTable {
+        name: String,
+        pub_key: Vec<u8>,
+        proof_key_ownership: Vec<u8>,
+    },
+    /// Submits the shuffled deck. Its proof is verified in stages: the first one runs right
+    /// away, the others with `VerifyShuffle`.
+    Shuffle {
+        deck: Vec<Vec<u8>>,
+        shuffle_proof: Vec<u8>,
+    },
+    /// Runs the next stage of the pending shuffle verification. Can be sent by any seated
+    /// player, but it is up to the shuffler to finish before their turn times out.
+    VerifyShuffle,
+    /// Stops accepting new players and starts the hand. The joint public key is computed
+    /// again if the seats changed since the previous hand.
+    /// Can be sent by any seated player once at least two seats are taken.
+    CloseRegistration,
+    /// Assigns the shuffled deck positions to the seats and the board.
+    /// Can be sent by any seated player once every seat has shuffled.
+    Deal,
+    /// A serialized reveal token for the card at `card_index` together with the proof
+    /// that it was computed with the sender's secret key. Tokens for the other players'
+    /// hole cards are due before the pre-flop betting opens. At showdown the players still
+    /// in the hand submit the tokens for their own hole cards.
+    SubmitRevealToken {
+        card_index: u8,
+        token: Vec<u8>,
+        proof: Vec<u8>,
+    },
+    /// Tokens for community cards that are not open yet, so a player who has folded does
+    /// not hold up the board. Only accepted from folded players: the tokens are public in
+    /// the message, and anyone holding every token of a card can open it ahead of its street.
+    /// They are verified right away and count once the street of their card comes. A player
+    /// can also send them with `Fold`.
+    PrecommitRevealTokens {
+        tokens: Vec<RevealTokenShare>,
+    },
+    Check,
+    /// Matches the current bet, or puts in the whole stack if it is smaller.
+    Call,
+    /// Opens the betting on a street. Must be at least the big blind unless it is all-in.
+    Bet {
+        amount: u128,
+    },
+    /// Raises the current bet to a street total of `to` chips. The increment must be at
+    /// least the previous bet or raise unless the raise is all-in.
+    Raise {
+        to: u128,
+    },
+    /// Gives up the hand. `tokens` are committed for community cards like with
+    /// `PrecommitRevealTokens`, in the same step, so a player who leaves the hand needs no
+    /// further message for the board. May be empty. Tokens are not taken at deal time, where
+    /// the last player to submit them would see the board before betting on it.
+    Fold {
+        tokens: Vec<RevealTokenShare>,
+    },
+    /// Gives up the sender's seat and credits their stack and deposit to their balance.
+    /// Only possible between hands.
+    LeaveTable,
+    /// Sends the sender's balance to them. Possible in any phase.
+    Withdraw,
+    /// Sent by the program to itself `turn_timeout` blocks after the table started waiting.
+    /// Ignored if the table has moved on since, rejected from anybody else.
+    Timeout {
+        timer: u64,
+    },
+}
+
+#[derive(Encode, Decode, TypeInfo, Debug, Clone, PartialEq, Eq)]
+pub enum GameEvent {
+    TableCreated {
+        game_id: GameId,
+    },
+    TableRetired {
+        game_id: GameId,
+    },
+    PlayerAdded {
+        player: ActorId,
+        seat: u8,
+    },
+    /// Registration is over. Also the reply to the `JoinTable` that takes the last seat.
+    RegistrationClosed {
+        joint_pk: Vec<u8>,
+    },
+    /// A stage of the shuffle proof passed, `verified_stages` out of `stages` are done.
+    ShuffleStageVerified {
+        player: ActorId,
+        verified_stages: u8,
+        stages: u8,
+    },
+    /// The last stage of the shuffle proof passed and the shuffled deck is accepted.
+    /// `next_shuffler` is `None` once every seat has shuffled.
+    DeckShuffled {
+        player: ActorId,
+        next_shuffler: Option<ActorId>,
+    },
+    /// Deck positions of every player's hole cards and of the community cards.
+    CardsDealt {
+        hole_cards: Vec<(ActorId, Vec<u8>)>,
+        community_cards: Vec<u8>,
+    },
+    RevealTokenAccepted {
+        player: ActorId,
+        card_index: u8,
+    },
+    /// The sender's tokens for the community cards at `card_indices` are held until their
+    /// streets open.
+    RevealTokensPrecommitted {
+        player: ActorId,
+        card_indices: Vec<u8>,
+    },
+    /// The last token for the current street has arrived and its community cards are open.
+    CommunityCardsOpened {
+        cards: Vec<(u8, ClassicPlayingCard)>,
+    },
+    /// The sender's chips after a betting action. `next_to_act` is `None` once the betting
+    /// round is over.
+    PlayerActed {
+        player: ActorId,
+        folded: bool,
+        bet: u128,
+        stack: u128,
+        next_to_act: Option<ActorId>,
+    },
+    /// Everybody else folded and `winner` collects the pot uncontested.
+    HandWon {
+        winner: ActorId,
+        amount: u128,
+    },
+    /// The hole cards of every player still in the hand are open, or the showdown timed
+    /// out. `hands` holds the opened hands with their category and `winnings` the chips
+    /// every winner collected. Hands that could not be opened and players who timed out
+    /// have no claim on the pots.
+    ShowdownSettled {
+        hands: Vec<(ActorId, Vec<ClassicPlayingCard>, HandCategory)>,
+        winnings: Vec<(ActorId, u128)>,
+    },
+    /// `player` left the table and `amount` was credited to their balance.
+    CashedOut {
+        player: ActorId,
+        amount: u128,
+    },
+    /// The balance of `player` was sent to them. Also the payload of that transfer.
+    Withdrawn {
+        player: ActorId,
+        amount: u128,
+    },
+    /// `players` missed the deadline: a stalled bettor is folded, while a stalled shuffler
+    /// and players holding back reveal tokens forfeit the hand to the others and lose their
+    /// seat. At showdown the hands that can be opened are settled first.
+    PlayersTimedOut {
+        players: Vec<ActorId>,
+    },
+    /// `player` sent a proof that does not verify. `amount` of their deposit is shared by
+    /// the other players, the hand is forfeited and the player loses their seat. A player
+    /// caught while joining is not seated and only loses the deposit.
+    PlayerSlashed {
+        player: ActorId,
+        reason: GameError,
+        amount: u128,
+    },
+}
+
+#[derive(Encode, Decode, TypeInfo, Debug, Clone, PartialEq, Eq)]
+pub enum GameError {
+    TableIsFull,
+    AlreadyRegistered,
+    InvalidPublicKey,
+    PublicKeyAlreadyUsed,
+    InvalidKeyOwnershipProof,
+    NotRegistered,
+    NotEnoughPlayers,
+    /// The table is not `Finished` or still holds balances that were not withdrawn.
+    TableInUse,
+    WrongPhase,
+    NotYourTurn,
+    UnknownTable,
+    MalformedMessage,
+    MalformedParameters,
+    MalformedDeck,
+    MalformedProof,
+    InvalidSeatCount,
+    InvalidShuffleProof,
+    /// The previous shuffle has to be verified before the next one is submitted.
+    ShuffleVerificationPending,
+    NoShuffleToVerify,
+    InvalidCardIndex,
+    OwnCardBeforeShowdown,
+    RevealTokenAlreadySubmitted,
+    MalformedRevealToken,
+    InvalidRevealProof,
+    StreetNotOpen,
+    CardAlreadyOpen,
+    UnknownCard,
+    InvalidBlinds,
+    InvalidBuyInLimits,
+    /// The value attached to `JoinTable` does not cover the deposit and a buy-in within the
+    /// table's limits.
+    InvalidBuyIn,
+    /// Value was attached to an action that does not take any. It is sent back.
+    UnexpectedValue,
+    InvalidTimeout,
+    /// `Timeout` was not sent by the program itself.
+    NotAllowed,
+    /// The table has moved on since the timeout was scheduled.
+    OutdatedTimeout,
+    CannotCheck,
+    NothingToCall,
+    BetAlreadyMade,
+    NoBetToRaise,
+    BetTooSmall,
+    NotEnoughChips,
+    RaiseNotAllowed,
+    /// Community card tokens can only be committed in advance once the sender has folded.
+    StillInHand,
+    NoRevealTokens,
+    /// Betting opens once every player has submitted the tokens for the others' hole cards.
+    WaitingForRevealTokens,
+    NothingToWithdraw,
+    /// Sending the balance failed, it stays claimable.
+    TransferFailed,
+    /// A failure reported by `proof_essentials`.
+    Crypto(String),
+    /// A failure reported by the card protocol.
+    CardProtocol(String),
+}
+
+#[derive(Encode, Decode, TypeInfo, Debug, Clone, PartialEq, Eq)]
+pub struct PlayerState {
+    pub id: ActorId,
+    pub name: String,
+    /// Serialized public key.
+    pub pk: Vec<u8>,
+    pub has_shuffled: bool,
+    /// Deck positions of the hole cards.
+    pub cards: Vec<u8>,
+    /// Chips behind.
+    pub stack: u128,
+    pub deposit: u128,
+    /// Chips put in on the current street.
+    pub bet: u128,
+    pub folded: bool,
+}
+
+#[derive(Encode, Decode, TypeInfo, Debug, Clone, PartialEq, Eq)]
+pub struct PotState {
+    pub amount: u128,
+    /// Players who can win the pot.
+    pub eligible: Vec<ActorId>,
+}
+
+#[derive(Encode, Decode, TypeInfo, Debug, Clone, PartialEq, Eq)]
+pub struct ShuffleVerificationState {
+    pub player: ActorId,
+    pub verified_stages: u8,
+    pub stages: u8,
+}
+
+/// Blake2s digest of data accepted by a table, see `HandRecord`.
+pub type Digest = [u8; 32];
+
+/// Entry of a table's append-only history.
+#[derive(Encode, Decode, TypeInfo, Debug, Clone, PartialEq, Eq)]
+pub struct HistoryEntry {
+    /// Hand the entry belongs to, counted from 0. Registrations count towards the hand
+    /// they are made before.
+    pub hand: u32,
+    /// Block height at which the entry was recorded.
+    pub block: u32,
+    pub record: HandRecord,
+}
+
+/// What the table accepted. Decks, proofs and tokens are only kept as digests, computed
+/// over the uncompressed canonical serialization of every element in turn. The data
+/// itself is in the messages the digests let anyone match.
+#[derive(Encode, Decode, TypeInfo, Debug, Clone, PartialEq, Eq)]
+pub enum HandRecord {
+    PlayerJoined {
+        player: ActorId,
+        seat: u8,
+        /// Digest of the public key and the key ownership proof.
+        pk: Digest,
+        buy_in: u128,
+    },
+    PlayerLeft {
+        player: ActorId,
+        amount: u128,
+    },
+    KeysAggregated {
+        joint_pk: Digest,
+        /// Digest of the masked deck the first shuffle starts from.
+        initial_deck: Digest,
+    },
+    DeckShuffled {
+        player: ActorId,
+        input_deck: Digest,
+        output_deck: Digest,
+        proof: Digest,
+    },
+    CardsDealt {
+        button: u8,
+        hole_cards: Vec<(ActorId, Vec<u8>)>,
+        community_cards: Vec<u8>,
+    },
+    RevealTokenAccepted {
+        player: ActorId,
+        card_index: u8,
+        /// Digest of the reveal token and its proof.
+        token: Digest,
+    },
+    CardsOpened {
+        cards: Vec<(u8, ClassicPlayingCard)>,
+    },
+    /// Hole cards opened at showdown and the category of the best five-card hand.
+    HandShown {
+        player: ActorId,
+        cards: Vec<ClassicPlayingCard>,
+        category: HandCategory,
+    },
+    PlayerActed {
+        player: ActorId,
+        action: BetAction,
+        /// The player's bet on the street and stack after the action.
+        bet: u128,
+        stack: u128,
+    },
+    PlayersTimedOut {
+        players: Vec<ActorId>,
+    },
+    PlayerSlashed {
+        player: ActorId,
+        reason: GameError,
+        amount: u128,
+    },
+    /// Chips a player took from the pots at the end of the hand.
+    PotCollected {
+        player: ActorId,
+        amount: u128,
+    },
+    /// Stack and deposit credited to the balance of a player losing their seat or at the end
+    /// of the session.
+    PaidOut {
+        player: ActorId,
+        amount: u128,
+    },
+}
+
+/// Betting actions as recorded in the history.
+#[derive(Encode, Decode, TypeInfo, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BetAction {
+    Check,
+    Call,
+    Bet { amount: u128 },
+    Raise { to: u128 },
+    Fold,
+}
+
+/// Snapshot of every table, returned by the `state()` query. The `sc-poker-state` functions
+/// read a single table out of it.
+#[derive(Encode, Decode, TypeInfo, Debug, Clone, PartialEq, Eq)]
+pub struct ProgramState {
+    pub tables: Vec<GameState>,
+}
+
+impl ProgramState {
+    pub fn table(&self, game_id: GameId) -> Option<&GameState> {
+        self.tables.iter().find(|table| table.game_id == game_id)
+    }
+}
+
+/// Snapshot of a table.
+#[derive(Encode, Decode, TypeInfo, Debug, Clone, PartialEq, Eq)]
+pub struct GameState {
+    pub game_id: GameId,
+    pub phase: Phase,
+    pub max_players: u8,
+    pub players: Vec<PlayerState>,
+    /// Serialized joint public key, set once registration is closed.
+    pub joint_pk: Option<Vec<u8>>,
+    /// Serialized masked cards of the current deck.
+    pub deck: Vec<Vec<u8>>,
+    /// Player the table is waiting for, if any.
+    pub turn: Option<ActorId>,
+    /// Deck positions of the flop, the turn and the river.
+    pub community_cards: Vec<u8>,
+    /// Deck positions opened so far with the cards behind them.
+    pub revealed_cards: Vec<(u8, ClassicPlayingCard)>,
+    /// Seat of the dealer button.
+    pub button: u8,
+    pub small_blind: u128,
+    pub big_blind: u128,
+    pub min_buy_in: u128,
+    pub max_buy_in: u128,
+    pub deposit: u128,
+    pub turn_timeout: u32,
+    /// Block height at which the player the table waits for times out.
+    pub deadline: Option<u32>,
+    /// Players caught with an invalid proof and the amount slashed from them.
+    pub slashed: Vec<(ActorId, u128)>,
+    /// Chips paid out and not withdrawn yet, by player.
+    pub balances: Vec<(ActorId, u128)>,
+    /// Progress of the shuffle being verified, if any.
+    pub shuffle_verification: Option<ShuffleVerificationState>,
+    /// Bet to match on the current street.
+    pub current_bet: u128,
+    /// Chips committed by all players during the hand.
+    pub pot: u128,
+    /// The main pot followed by the side pots created by all-in players.
+    pub pots: Vec<PotState>,
+    /// Number of the current hand, counted from 0.
+    pub hand: u32,
+    /// Everything the table has accepted, oldest first. The `hand_history` state function
+    /// of `sc-poker-state` reads a single hand.
+    pub history: Vec<HistoryEntry>,
+}
+
+impl GameState {
+    /// The records of one hand, in the order they were accepted.
+    pub fn hand_history(&self, hand: u32) -> impl Iterator<Item = &HandRecord> {
+        self.history
+            .iter()
+            .filter(move |entry| entry.hand == hand)
+            .map(|entry| &entry.record)
+    }
+}
+
+impl From<CryptoError> for GameError {
+    fn from(error: CryptoError) -> Self {
+        Self::Crypto(error.to_string())
+    }
+}
+
+impl From<CardProtocolError> for GameError {
+    fn from(error: CardProtocolError) -> Self {
+        Self::CardProtocol(error.to_string())
+    }
+}
@@ -0,0 +1,91 @@
+//! Messages and state of the lobby program, which deploys one table program per game.
+
+use crate::{GameError, GameId, InitGame, Phase};
+use gmeta::{In, InOut, Metadata};
+use gstd::{prelude::*, ActorId, CodeId};
+
+pub struct LobbyMetadata;
+
+impl Metadata for LobbyMetadata {
+    type Init = In<LobbyInit>;
+    type Handle = InOut<LobbyAction, Result<LobbyEvent, LobbyError>>;
+    type Others = ();
+    /// Replies of the tables to their init message.
+    type Reply = Result<(), GameError>;
+    type Signal = ();
+    type State = LobbyState;
+}
+
+#[derive(Encode, Decode, TypeInfo)]
+pub struct LobbyInit {
+    /// Code of the `sc_poker` program every table is deployed from.
+    pub table_code_id: CodeId,
+}
+
+#[derive(Encode, Decode, TypeInfo)]
+pub enum LobbyAction {
+    /// Deploys a table program with these parameters. The reply is `TableCreated` with
+    /// its address, players join the table there. The table is dropped from the list
+    /// again if it rejects the parameters.
+    CreateTable(InitGame),
+    /// Sent by a table deployed by the lobby whenever its phase or seat count changes.
+    TableUpdated(TableStatus),
+}
+
+#[derive(Encode, Decode, TypeInfo, Debug, Clone, PartialEq, Eq)]
+pub struct TableStatus {
+    pub game_id: GameId,
+    pub phase: Phase,
+    pub seats_taken: u8,
+}
+
+#[derive(Encode, Decode, TypeInfo, Debug, Clone, PartialEq, Eq)]
+pub enum LobbyEvent {
+    TableCreated { program: ActorId },
+    TableUpdated { program: ActorId },
+}
+
+#[derive(Encode, Decode, TypeInfo, Debug, Clone, PartialEq, Eq)]
+pub enum LobbyError {
+    MalformedMessage,
+    UnexpectedValue,
+    ProgramCreationFailed,
+    /// The update does not come from the table a program was deployed for.
+    UnknownTable,
+}
+
+/// Snapshot of the lobby, returned by the `state()` query.
+#[derive(Encode, Decode, TypeInfo, Debug, Clone, PartialEq, Eq)]
+pub struct LobbyState {
+    pub table_code_id: CodeId,
+    pub tables: Vec<TableInfo>,
+}
+
+impl LobbyState {
+    /// Tables still taking players.
+    pub fn open_tables(&self) -> impl Iterator<Item = &TableInfo> {
+        self.tables.iter().filter(|table| table.is_open())
+    }
+}
+
+#[derive(Encode, Decode, TypeInfo, Debug, Clone, PartialEq, Eq)]
+pub struct TableInfo {
+    /// Address of the table program, `GameMessage`s go there with `game_id` 0.
+    pub program: ActorId,
+    pub creator: ActorId,
+    /// Last phase reported by the table, `None` until its first report arrives.
+    pub phase: Option<Phase>,
+    pub seats_taken: u8,
+    pub max_players: u8,
+    pub small_blind: u128,
+    pub big_blind: u128,
+    pub min_buy_in: u128,
+    pub max_buy_in: u128,
+    pub deposit: u128,
+}
+
+impl TableInfo {
+    pub fn is_open(&self) -> bool {
+        self.phase == Some(Phase::Registration) && self.seats_taken < self.max_players
+    }
+}
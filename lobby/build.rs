@@ -0,0 +1,5 @@
+use sc_poker_io::lobby::LobbyMetadata;
+
+fn main() {
+    gear_wasm_builder::build_with_metadata::<LobbyMetadata>();
+}
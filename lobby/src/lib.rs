@@ -0,0 +1,128 @@
+#![no_std]
+
+//! Lobby that deploys every table as its own `sc_poker` program and keeps a directory of
+//! them, so players can find a table with free seats and join it there.
+
+use gstd::{exec, msg, prelude::*, prog::ProgramGenerator, ActorId, CodeId, MessageId};
+use sc_poker_io::lobby::*;
+use sc_poker_io::{GameError, InitGame};
+
+struct Lobby {
+    table_code_id: CodeId,
+    /// Tables by the address of their program.
+    tables: BTreeMap<ActorId, TableInfo>,
+    /// Programs whose init message has not been replied to yet, by that message.
+    pending: BTreeMap<MessageId, ActorId>,
+}
+
+impl Lobby {
+    fn create_table(&mut self, mut init_msg: InitGame) -> Result<LobbyEvent, LobbyError> {
+        if msg::value() > 0 {
+            return Err(LobbyError::UnexpectedValue);
+        }
+
+        init_msg.lobby = Some(exec::program_id());
+        let info = TableInfo {
+            program: ActorId::zero(),
+            creator: msg::source(),
+            phase: None,
+            seats_taken: 0,
+            max_players: init_msg.max_players,
+            small_blind: init_msg.small_blind,
+            big_blind: init_msg.big_blind,
+            min_buy_in: init_msg.min_buy_in,
+            max_buy_in: init_msg.max_buy_in,
+            deposit: init_msg.deposit,
+        };
+        // The parameters are checked by the table itself, the table is dropped again if
+        // its init does not succeed.
+        let (init_msg_id, program) =
+            ProgramGenerator::create_program(self.table_code_id, init_msg, 0)
+                .map_err(|_| LobbyError::ProgramCreationFailed)?;
+        self.tables.insert(program, TableInfo { program, ..info });
+        self.pending.insert(init_msg_id, program);
+
+        Ok(LobbyEvent::TableCreated { program })
+    }
+
+    /// Handles the reply to a table's init message: the table stays listed only if it was
+    /// set up.
+    fn table_initialized(&mut self, init_msg_id: MessageId, reply: Option<Result<(), GameError>>) {
+        let program = match self.pending.remove(&init_msg_id) {
+            Some(program) => program,
+            None => return,
+        };
+        if reply != Some(Ok(())) {
+            self.tables.remove(&program);
+        }
+    }
+
+    fn update_table(&mut self, status: TableStatus) -> Result<LobbyEvent, LobbyError> {
+        let program = msg::source();
+        // Only the table set up by the program's `init` is listed.
+        let table = match self.tables.get_mut(&program) {
+            Some(table) if status.game_id == 0 => table,
+            _ => return Err(LobbyError::UnknownTable),
+        };
+        table.phase = Some(status.phase);
+        table.seats_taken = status.seats_taken;
+
+        Ok(LobbyEvent::TableUpdated { program })
+    }
+}
+
+static mut LOBBY: Option<Lobby> = None;
+
+#[no_mangle]
+extern "C" fn handle() {
+    let result = process_handle();
+    let refund = if result.is_err() { msg::value() } else { 0 };
+    msg::reply(result, refund).expect("Unable to reply");
+}
+
+fn process_handle() -> Result<LobbyEvent, LobbyError> {
+    let action: LobbyAction = msg::load().map_err(|_| LobbyError::MalformedMessage)?;
+    let lobby = unsafe { LOBBY.as_mut().expect("The lobby is not initialized") };
+
+    match action {
+        LobbyAction::CreateTable(init_msg) => lobby.create_table(init_msg),
+        LobbyAction::TableUpdated(status) => lobby.update_table(status),
+    }
+}
+
+#[no_mangle]
+extern "C" fn handle_reply() {
+    let lobby = unsafe { LOBBY.as_mut().expect("The lobby is not initialized") };
+    let init_msg_id = msg::reply_to().expect("Unable to get the replied message");
+    // A table whose init panicked replies with an error that does not decode.
+    let reply = msg::load::<Result<(), GameError>>().ok();
+    lobby.table_initialized(init_msg_id, reply);
+}
+
+#[no_mangle]
+extern "C" fn init() {
+    let init_msg: LobbyInit = msg::load().expect("Unable to decode `LobbyInit`");
+    unsafe {
+        LOBBY = Some(Lobby {
+            table_code_id: init_msg.table_code_id,
+            tables: BTreeMap::new(),
+            pending: BTreeMap::new(),
+        })
+    };
+}
+
+#[no_mangle]
+extern "C" fn state() {
+    let lobby = unsafe { LOBBY.as_ref().expect("The lobby is not initialized") };
+    let state = LobbyState {
+        table_code_id: lobby.table_code_id,
+        tables: lobby.tables.values().cloned().collect(),
+    };
+    msg::reply(state, 0).expect("Unable to share the state");
+}
+
+#[no_mangle]
+extern "C" fn metahash() {
+    let metahash: [u8; 32] = include!("../.metahash");
+    msg::reply(metahash, 0).expect("Unable to share the metahash");
+}
@@ -0,0 +1,154 @@
+use ark_serialize::CanonicalSerialize;
+use barnett_smart_card_protocol::discrete_log_cards;
+use barnett_smart_card_protocol::BarnettSmartProtocol;
+use gstd::ActorId;
+use gtest::{Log, Program, System};
+use rand::thread_rng;
+use sc_poker_io::encoding::Curve;
+use sc_poker_io::lobby::*;
+use sc_poker_io::{GameAction, GameMessage, InitGame, Phase};
+
+type CardProtocol<'a> = discrete_log_cards::DLCards<'a, Curve>;
+type CardParameters = discrete_log_cards::Parameters<Curve>;
+
+/// The table program, built by `cargo build --release` in the repository root.
+const TABLE_WASM: &str = "../target/wasm32-unknown-unknown/release/sc_poker.opt.wasm";
+const ADMIN: u64 = 100;
+const CREATOR: u64 = 101;
+const PLAYER: u64 = 102;
+
+fn init_lobby(system: &System) -> Program<'_> {
+    let lobby = Program::current(system);
+    let table_code_id = system.submit_code(TABLE_WASM).into_bytes().into();
+    let result = lobby.send(ADMIN, LobbyInit { table_code_id });
+    assert!(!result.main_failed());
+    lobby
+}
+
+fn init_game() -> InitGame {
+    let parameters = CardProtocol::setup(&mut thread_rng(), 2, 26).unwrap();
+    init_game_with(&parameters)
+}
+
+fn init_game_with(parameters: &CardParameters) -> InitGame {
+    let mut enc_parameters = Vec::new();
+    let mut commit_parameters = Vec::new();
+    let mut generator = Vec::new();
+    parameters
+        .enc_parameters
+        .serialize_uncompressed(&mut enc_parameters)
+        .unwrap();
+    parameters
+        .commit_parameters
+        .serialize_uncompressed(&mut commit_parameters)
+        .unwrap();
+    parameters
+        .generator
+        .serialize_uncompressed(&mut generator)
+        .unwrap();
+
+    InitGame {
+        enc_parameters,
+        commit_parameters,
+        generator,
+        max_players: 2,
+        small_blind: 1,
+        big_blind: 2,
+        min_buy_in: 100,
+        max_buy_in: 1000,
+        deposit: 50,
+        turn_timeout: 100,
+        lobby: None,
+    }
+}
+
+#[test]
+fn lists_a_created_table() {
+    let system = System::new();
+    system.init_logger();
+    let lobby = init_lobby(&system);
+
+    let result = lobby.send(CREATOR, LobbyAction::CreateTable(init_game()));
+    assert!(!result.main_failed());
+
+    // The table reported its phase to the lobby while it was set up.
+    let state: LobbyState = lobby.read_state().unwrap();
+    assert_eq!(state.tables.len(), 1);
+    let table = &state.tables[0];
+    assert_eq!(table.creator, CREATOR.into());
+    assert_eq!(table.phase, Some(Phase::Registration));
+    assert_eq!(table.seats_taken, 0);
+    assert_eq!(state.open_tables().count(), 1);
+
+    let reply: Result<LobbyEvent, LobbyError> = Ok(LobbyEvent::TableCreated {
+        program: table.program,
+    });
+    assert!(result.contains(&Log::builder().dest(CREATOR).payload(reply)));
+}
+
+#[test]
+fn drops_a_table_that_rejects_its_parameters() {
+    let system = System::new();
+    system.init_logger();
+    let lobby = init_lobby(&system);
+
+    let init_msg = InitGame {
+        max_players: 1,
+        ..init_game()
+    };
+    let result = lobby.send(CREATOR, LobbyAction::CreateTable(init_msg));
+    assert!(!result.main_failed());
+
+    let state: LobbyState = lobby.read_state().unwrap();
+    assert!(state.tables.is_empty());
+}
+
+#[test]
+fn tracks_the_seats_taken() {
+    let system = System::new();
+    system.init_logger();
+    let lobby = init_lobby(&system);
+    let rng = &mut thread_rng();
+    let parameters = CardProtocol::setup(rng, 2, 26).unwrap();
+
+    let result = lobby.send(
+        CREATOR,
+        LobbyAction::CreateTable(init_game_with(&parameters)),
+    );
+    assert!(!result.main_failed());
+    let state: LobbyState = lobby.read_state().unwrap();
+    let mut program = [0u8; 32];
+    program.copy_from_slice(state.tables[0].program.as_ref());
+    let table = system.get_program(program);
+
+    let (pk, sk) = CardProtocol::player_keygen(rng, &parameters).unwrap();
+    let mut public_info = [0u8; 32];
+    public_info.copy_from_slice(ActorId::from(PLAYER).as_ref());
+    let proof =
+        CardProtocol::prove_key_ownership(rng, &parameters, &pk, &sk, &public_info).unwrap();
+    let mut pub_key = Vec::new();
+    pk.serialize_uncompressed(&mut pub_key).unwrap();
+    let mut proof_key_ownership = Vec::new();
+    proof
+        .serialize_uncompressed(&mut proof_key_ownership)
+        .unwrap();
+
+    system.mint_to(PLAYER, 150);
+    let join = GameMessage::Action {
+        game_id: 0,
+        action: GameAction::JoinTable {
+            name: "Alice".into(),
+            pub_key,
+            proof_key_ownership,
+        },
+    };
+    let result = table.send_with_value(PLAYER, join, 150);
+    assert!(!result.main_failed());
+
+    // The join does not change the phase, the seat count is reported all the same.
+    let state: LobbyState = lobby.read_state().unwrap();
+    let table = &state.tables[0];
+    assert_eq!(table.phase, Some(Phase::Registration));
+    assert_eq!(table.seats_taken, 1);
+    assert_eq!(state.open_tables().count(), 1);
+}
@@ -28,6 +28,15 @@ where
     pub multi_exp_proof: multi_exponentiation::proof::Proof<Scalar, Enc, Comm>,
 }
 
+/// Challenges of the shuffle argument, derived from the commitments during the first stage of
+/// the verification and used by the product and the multi-exponentiation arguments.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Challenges<Scalar: Field> {
+    pub x: Scalar,
+    pub y: Scalar,
+    pub z: Scalar,
+}
+
 impl<Scalar, Enc, Comm> Proof<Scalar, Enc, Comm>
 where
     Scalar: Field,
@@ -40,6 +49,22 @@ where
         statement: &Statement<Scalar, Enc>,
         fs_rng: &mut FiatShamirRng<D>,
     ) -> Result<(), CryptoError> {
+        let challenges = self.verify_commitments(proof_parameters, statement, fs_rng)?;
+        self.verify_product_argument(proof_parameters, statement, &challenges, fs_rng)?;
+        self.verify_multi_exponentiation(proof_parameters, statement, &challenges, fs_rng)
+    }
+
+    /// First stage of the verification: checks the statement and absorbs it together with the
+    /// commitments to derive the challenges.
+    ///
+    /// The stages can run separately, e.g. to spread the verification over several calls, as
+    /// long as they run in order on the same `fs_rng`.
+    pub fn verify_commitments<D: Digest>(
+        &self,
+        proof_parameters: &Parameters<Scalar, Enc, Comm>,
+        statement: &Statement<Scalar, Enc>,
+        fs_rng: &mut FiatShamirRng<D>,
+    ) -> Result<Challenges<Scalar>, CryptoError> {
         statement.is_valid()?;
 
         fs_rng.absorb(&to_bytes![b"shuffle_argument"]?);
@@ -65,14 +90,25 @@ where
         fs_rng.absorb(&to_bytes![self.a_commits]?);
         let x = Scalar::rand(fs_rng);
 
-        let challenge_powers = scalar_powers(x, statement.m * statement.n)[1..].to_vec();
-
         // round 2
         fs_rng.absorb(&to_bytes![self.b_commits]?);
         let y = Scalar::rand(fs_rng);
         let z = Scalar::rand(fs_rng);
 
-        // PRODUCT ARGUMENT -------------------------------------------------------------
+        Ok(Challenges { x, y, z })
+    }
+
+    /// Second stage of the verification: the product argument.
+    pub fn verify_product_argument<D: Digest>(
+        &self,
+        proof_parameters: &Parameters<Scalar, Enc, Comm>,
+        statement: &Statement<Scalar, Enc>,
+        challenges: &Challenges<Scalar>,
+        fs_rng: &mut FiatShamirRng<D>,
+    ) -> Result<(), CryptoError> {
+        let Challenges { x, y, z } = *challenges;
+        let challenge_powers = scalar_powers(x, statement.m * statement.n)[1..].to_vec();
+
         let z_vec = vec![-z; statement.n];
         let zero = Scalar::zero();
         let single_neg_z_commit = Comm::commit(proof_parameters.commit_key, &z_vec, zero)?;
@@ -108,9 +144,19 @@ where
             &product_argument_parameters,
             &product_argument_statement,
             fs_rng,
-        )?;
+        )
+    }
+
+    /// Third and last stage of the verification: the multi-exponentiation argument.
+    pub fn verify_multi_exponentiation<D: Digest>(
+        &self,
+        proof_parameters: &Parameters<Scalar, Enc, Comm>,
+        statement: &Statement<Scalar, Enc>,
+        challenges: &Challenges<Scalar>,
+        fs_rng: &mut FiatShamirRng<D>,
+    ) -> Result<(), CryptoError> {
+        let challenge_powers = scalar_powers(challenges.x, statement.m * statement.n)[1..].to_vec();
 
-        // MULTI-EXPONENTIATION ARGUMENT -------------------------------------------------------
         let multi_exp_parameters = multi_exponentiation::Parameters::new(
             proof_parameters.encrypt_parameters,
             proof_parameters.public_key,
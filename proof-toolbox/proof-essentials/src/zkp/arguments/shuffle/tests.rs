@@ -93,4 +93,66 @@ mod test {
             ShuffleArgument::verify(&parameters, &statement, &invalid_proof, &mut fs_rng)
         );
     }
+
+    #[test]
+    fn test_staged_verification() {
+        let m = 2;
+        let n = 4;
+        let number_of_ciphers = n * m;
+
+        let rng = &mut thread_rng();
+
+        let encrypt_parameters = Enc::setup(rng).unwrap();
+        let (pk, _) = Enc::keygen(&encrypt_parameters, rng).unwrap();
+        let commit_key = Comm::setup(rng, n);
+        let generator = Generator::rand(rng);
+
+        let ciphers: Vec<Ciphertext> = sample_vector(rng, number_of_ciphers);
+        let masking_factors: Vec<Scalar> = sample_vector(rng, number_of_ciphers);
+        let permutation = Permutation::new(rng, number_of_ciphers);
+
+        let shuffled_deck = permutation
+            .permute_array(&ciphers)
+            .iter()
+            .zip(masking_factors.iter())
+            .map(|(&cipher, masking_factor)| {
+                let zero_cipher = Plaintext::zero();
+                let masking_cipher =
+                    Enc::encrypt(&encrypt_parameters, &pk, &zero_cipher, masking_factor).unwrap();
+
+                cipher + masking_cipher
+            })
+            .collect::<Vec<_>>();
+
+        let parameters = Parameters::new(&encrypt_parameters, &pk, &commit_key, &generator);
+        let statement = Statement::new(&ciphers, &shuffled_deck, m, n);
+        let witness = Witness::new(&permutation, &masking_factors);
+
+        let mut fs_rng = FS::from_seed(b"Initialised with some input");
+        let proof =
+            ShuffleArgument::prove(rng, &parameters, &statement, &witness, &mut fs_rng).unwrap();
+
+        let mut fs_rng = FS::from_seed(b"Initialised with some input");
+        let challenges = proof
+            .verify_commitments(&parameters, &statement, &mut fs_rng)
+            .unwrap();
+        assert_eq!(
+            Ok(()),
+            proof.verify_product_argument(&parameters, &statement, &challenges, &mut fs_rng)
+        );
+        assert_eq!(
+            Ok(()),
+            proof.verify_multi_exponentiation(&parameters, &statement, &challenges, &mut fs_rng)
+        );
+
+        // Skipping a stage leaves the transcript out of sync with the prover's.
+        let mut fs_rng = FS::from_seed(b"Initialised with some input");
+        let challenges = proof
+            .verify_commitments(&parameters, &statement, &mut fs_rng)
+            .unwrap();
+        assert_ne!(
+            Ok(()),
+            proof.verify_multi_exponentiation(&parameters, &statement, &challenges, &mut fs_rng)
+        );
+    }
 }
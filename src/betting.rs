@@ -0,0 +1,280 @@
+//! No-limit betting rounds: blinds, action order, minimum raises and all-ins.
+//!
+//! Seats are indexed like `Game::players`. This module only moves chips from stacks into
+//! bets; the game decides when a street starts and who collects the pot.
+
+use crate::pot::{self, Pot};
+use gstd::prelude::*;
+use sc_poker_io::{BetAction, GameError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Check,
+    Call,
+    /// Opens the betting on a street with `amount` chips.
+    Bet(u128),
+    /// Raises the current bet to a street total of `to` chips.
+    Raise(u128),
+    Fold,
+}
+
+impl From<Action> for BetAction {
+    fn from(action: Action) -> Self {
+        match action {
+            Action::Check => Self::Check,
+            Action::Call => Self::Call,
+            Action::Bet(amount) => Self::Bet { amount },
+            Action::Raise(to) => Self::Raise { to },
+            Action::Fold => Self::Fold,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Seat {
+    /// Chips behind.
+    pub stack: u128,
+    /// Chips put in on the current street.
+    pub bet: u128,
+    /// Chips put in during the whole hand.
+    pub committed: u128,
+    pub folded: bool,
+    /// Whether the seat has acted since the last full bet or raise.
+    acted: bool,
+    /// Cleared when the only raise since the seat last acted was an incomplete all-in,
+    /// which does not reopen the betting.
+    may_raise: bool,
+}
+
+impl Seat {
+    pub fn is_all_in(&self) -> bool {
+        !self.folded && self.stack == 0
+    }
+
+    fn can_act(&self) -> bool {
+        !self.folded && self.stack > 0
+    }
+
+    fn put_in(&mut self, amount: u128) {
+        self.stack -= amount;
+        self.bet += amount;
+        self.committed += amount;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Betting {
+    pub seats: Vec<Seat>,
+    pub button: usize,
+    big_blind: u128,
+    /// The bet to match on the current street.
+    pub current_bet: u128,
+    /// Size of the last full bet or raise, the smallest increment allowed for the next one.
+    pub min_raise: u128,
+    /// Seat expected to act, `None` once the round is over.
+    pub to_act: Option<usize>,
+}
+
+impl Betting {
+    /// Starts a hand: posts the blinds and hands the action to the seat after the big blind.
+    /// Heads-up the button posts the small blind, acts first before the flop and last after.
+    pub fn new(stacks: &[u128], button: usize, small_blind: u128, big_blind: u128) -> Self {
+        let num_of_seats = stacks.len();
+        let seats = stacks
+            .iter()
+            .map(|&stack| Seat {
+                stack,
+                may_raise: true,
+                ..Default::default()
+            })
+            .collect();
+
+        let mut betting = Self {
+            seats,
+            button,
+            big_blind,
+            current_bet: big_blind,
+            min_raise: big_blind,
+            to_act: None,
+        };
+
+        let (small, big) = if num_of_seats == 2 {
+            (button, (button + 1) % num_of_seats)
+        } else {
+            ((button + 1) % num_of_seats, (button + 2) % num_of_seats)
+        };
+        betting.post(small, small_blind);
+        betting.post(big, big_blind);
+        betting.to_act = betting.next_to_act(big);
+
+        betting
+    }
+
+    /// Resets the street bets for the flop, the turn or the river. The first seat after the
+    /// button still able to bet opens the action.
+    pub fn next_street(&mut self) {
+        for seat in self.seats.iter_mut() {
+            seat.bet = 0;
+            seat.acted = false;
+            seat.may_raise = true;
+        }
+        self.current_bet = 0;
+        self.min_raise = self.big_blind;
+        self.to_act = self.next_to_act(self.button);
+    }
+
+    pub fn act(&mut self, seat: usize, action: Action) -> Result<(), GameError> {
+        if self.to_act != Some(seat) {
+            return Err(GameError::NotYourTurn);
+        }
+
+        let to_call = self.current_bet - self.seats[seat].bet;
+        match action {
+            Action::Check if to_call > 0 => return Err(GameError::CannotCheck),
+            Action::Check => {}
+            Action::Call if to_call == 0 => return Err(GameError::NothingToCall),
+            Action::Call => {
+                let player = &mut self.seats[seat];
+                player.put_in(to_call.min(player.stack));
+            }
+            Action::Bet(_) if self.current_bet > 0 => return Err(GameError::BetAlreadyMade),
+            Action::Raise(_) if self.current_bet == 0 => return Err(GameError::NoBetToRaise),
+            Action::Bet(to) | Action::Raise(to) => self.raise_to(seat, to)?,
+            Action::Fold => self.seats[seat].folded = true,
+        }
+
+        self.seats[seat].acted = true;
+        self.to_act = self.next_to_act(seat);
+
+        Ok(())
+    }
+
+    /// Chips committed by every seat during the hand.
+    pub fn pot(&self) -> u128 {
+        self.seats.iter().map(|seat| seat.committed).sum()
+    }
+
+    /// The main pot and the side pots over the chips committed so far.
+    pub fn pots(&self) -> Vec<Pot> {
+        pot::side_pots(&self.seats)
+    }
+
+    /// The only seat that has not folded, if everybody else has.
+    pub fn last_player_standing(&self) -> Option<usize> {
+        let mut remaining = self
+            .seats
+            .iter()
+            .enumerate()
+            .filter(|(_, seat)| !seat.folded)
+            .map(|(index, _)| index);
+        match (remaining.next(), remaining.next()) {
+            (Some(index), None) => Some(index),
+            _ => None,
+        }
+    }
+
+    /// Folds `seat` out of turn, when it no longer takes part in the hand.
+    pub fn forfeit(&mut self, seat: usize) {
+        self.seats[seat].folded = true;
+        self.to_act = None;
+    }
+
+    /// Adds the chips won by every seat to their stacks and clears the pot.
+    pub fn settle(&mut self, winnings: &[u128]) {
+        for (seat, won) in self.seats.iter_mut().zip(winnings) {
+            seat.stack += won;
+            seat.bet = 0;
+            seat.committed = 0;
+        }
+        self.to_act = None;
+    }
+
+    /// Moves every committed chip to the stack of `winner` and returns the amount won.
+    pub fn award_pot(&mut self, winner: usize) -> u128 {
+        let pot = self.pot();
+        for seat in self.seats.iter_mut() {
+            seat.bet = 0;
+            seat.committed = 0;
+        }
+        self.seats[winner].stack += pot;
+        self.to_act = None;
+        pot
+    }
+
+    fn post(&mut self, seat: usize, blind: u128) {
+        let player = &mut self.seats[seat];
+        player.put_in(blind.min(player.stack));
+    }
+
+    fn raise_to(&mut self, seat: usize, to: u128) -> Result<(), GameError> {
+        let player = &self.seats[seat];
+        let opponent_can_act = self
+            .seats
+            .iter()
+            .enumerate()
+            .any(|(index, other)| index != seat && other.can_act());
+        if !player.may_raise || !opponent_can_act {
+            return Err(GameError::RaiseNotAllowed);
+        }
+        if to <= self.current_bet {
+            return Err(GameError::BetTooSmall);
+        }
+
+        let amount = to - player.bet;
+        if amount > player.stack {
+            return Err(GameError::NotEnoughChips);
+        }
+        let is_all_in = amount == player.stack;
+        let increment = to - self.current_bet;
+        let is_full_raise = increment >= self.min_raise;
+        if !is_full_raise && !is_all_in {
+            return Err(GameError::BetTooSmall);
+        }
+
+        self.seats[seat].put_in(amount);
+        self.current_bet = to;
+        if is_full_raise {
+            self.min_raise = increment;
+        }
+
+        for (index, other) in self.seats.iter_mut().enumerate() {
+            if index == seat {
+                continue;
+            }
+            if is_full_raise {
+                other.acted = false;
+                other.may_raise = true;
+            } else if other.acted {
+                other.acted = false;
+                other.may_raise = false;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn next_to_act(&self, after: usize) -> Option<usize> {
+        let num_of_seats = self.seats.len();
+        (1..=num_of_seats)
+            .map(|offset| (after + offset) % num_of_seats)
+            .find(|&seat| self.needs_action(seat))
+    }
+
+    fn needs_action(&self, seat: usize) -> bool {
+        let player = &self.seats[seat];
+        if !player.can_act() || self.last_player_standing().is_some() {
+            return false;
+        }
+        if player.bet < self.current_bet {
+            return true;
+        }
+
+        // Nobody is left to bet against once every opponent has folded or is all-in.
+        !player.acted
+            && self
+                .seats
+                .iter()
+                .enumerate()
+                .any(|(index, other)| index != seat && other.can_act())
+    }
+}
@@ -0,0 +1,150 @@
+//! Poker hand evaluation for the showdown.
+//!
+//! A hand is ranked by its category and then by the values that break ties within the
+//! category, so two `HandRank`s compare exactly like the hands they describe.
+
+use gstd::prelude::*;
+use sc_poker_io::{ClassicPlayingCard, HandCategory, Value};
+
+const HAND_SIZE: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct HandRank {
+    pub category: HandCategory,
+    /// Card values in order of significance: grouped cards first (the quads, the trips
+    /// of a full house, the higher pair, ...), then the kickers from high to low.
+    /// For straights the top card comes first, so a wheel starts with `Five`.
+    pub values: [Value; HAND_SIZE],
+}
+
+/// Ranks the best five-card hand out of five to seven cards.
+pub fn evaluate(cards: &[ClassicPlayingCard]) -> HandRank {
+    assert!(
+        (HAND_SIZE..=7).contains(&cards.len()),
+        "A hand is made of five to seven cards"
+    );
+
+    let mut best: Option<HandRank> = None;
+    for mask in 0u8..(1 << cards.len()) {
+        if mask.count_ones() as usize != HAND_SIZE {
+            continue;
+        }
+
+        let mut hand = [cards[0]; HAND_SIZE];
+        let mut selected = (0..cards.len()).filter(|i| mask & (1 << i) != 0);
+        for card in hand.iter_mut() {
+            *card = cards[selected.next().expect("Exactly five bits are set")];
+        }
+
+        let rank = rank_five(&hand);
+        if best < Some(rank) {
+            best = Some(rank);
+        }
+    }
+
+    best.expect("There is at least one five-card combination")
+}
+
+/// Ranks exactly five cards.
+pub fn rank_five(hand: &[ClassicPlayingCard; HAND_SIZE]) -> HandRank {
+    let mut values = hand.map(|card| card.value);
+    values.sort_unstable_by(|a, b| b.cmp(a));
+
+    let is_flush = hand.iter().all(|card| card.suite == hand[0].suite);
+    let straight_high = straight_high(&values);
+
+    if let Some(high) = straight_high {
+        let values = straight_values(high);
+        let category = match (is_flush, high) {
+            (true, Value::Ace) => HandCategory::RoyalFlush,
+            (true, _) => HandCategory::StraightFlush,
+            (false, _) => HandCategory::Straight,
+        };
+        return HandRank { category, values };
+    }
+
+    // Group equal values, the larger groups first and the higher values first within
+    // groups of the same size.
+    let mut groups: Vec<(usize, Value)> = Vec::with_capacity(HAND_SIZE);
+    for value in values {
+        match groups.iter_mut().find(|(_, grouped)| *grouped == value) {
+            Some((count, _)) => *count += 1,
+            None => groups.push((1, value)),
+        }
+    }
+    groups.sort_unstable_by(|a, b| b.cmp(a));
+
+    let mut ordered = values;
+    let mut position = 0;
+    for &(count, value) in &groups {
+        for _ in 0..count {
+            ordered[position] = value;
+            position += 1;
+        }
+    }
+
+    let category = match (groups[0].0, groups.get(1).map(|group| group.0)) {
+        (4, _) => HandCategory::FourOfAKind,
+        (3, Some(2)) => HandCategory::FullHouse,
+        _ if is_flush => HandCategory::Flush,
+        (3, _) => HandCategory::ThreeOfAKind,
+        (2, Some(2)) => HandCategory::TwoPair,
+        (2, _) => HandCategory::OnePair,
+        _ => HandCategory::HighCard,
+    };
+
+    HandRank {
+        category,
+        values: ordered,
+    }
+}
+
+/// Indices of the best hands. More than one index means an exact tie and a split pot.
+pub fn winners(hands: &[HandRank]) -> Vec<usize> {
+    let best = match hands.iter().max() {
+        Some(best) => best,
+        None => return Vec::new(),
+    };
+
+    hands
+        .iter()
+        .enumerate()
+        .filter(|(_, hand)| *hand == best)
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// The top card of a straight made by five values sorted from high to low.
+fn straight_high(values: &[Value; HAND_SIZE]) -> Option<Value> {
+    let ranks = values.map(|value| value as u8);
+    if ranks.windows(2).all(|pair| pair[0] == pair[1] + 1) {
+        return Some(values[0]);
+    }
+
+    // The wheel: A-5-4-3-2, where the ace plays low.
+    let wheel = [
+        Value::Ace,
+        Value::Five,
+        Value::Four,
+        Value::Three,
+        Value::Two,
+    ];
+    if *values == wheel {
+        return Some(Value::Five);
+    }
+
+    None
+}
+
+fn straight_values(high: Value) -> [Value; HAND_SIZE] {
+    let high = high as usize;
+    let mut values = [Value::Ace; HAND_SIZE];
+    for (offset, value) in values.iter_mut().enumerate() {
+        // Below `Two` the wheel wraps around to the ace.
+        *value = match high.checked_sub(offset) {
+            Some(rank) => Value::VALUES[rank],
+            None => Value::Ace,
+        };
+    }
+    values
+}
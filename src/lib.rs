@@ -2,10 +2,13 @@
 use anyhow;
 
 use ark_bn254::{Bn254, Fr, G1Affine, G1Projective as G1, G2Affine, G2Projective as G2};
-use ark_ff::Field;
+use ark_ff::{Field, One};
 use barnett_smart_card_protocol_for_sc::discrete_log_cards;
+use barnett_smart_card_protocol_for_sc::discrete_log_cards::ShuffleVerifier;
 use barnett_smart_card_protocol_for_sc::BarnettSmartProtocol;
-use gstd::{msg, prelude::*, debug, exec};
+use barnett_smart_card_protocol_for_sc::Mask;
+use blake2::{Blake2s, Digest as _};
+use gstd::{exec, msg, prelude::*, ActorId};
 use proof_essentials::vector_commitment::pedersen::CommitKey;
 //use ark_bls12_377::{ G1Affine, G1Projective as G1, G2Affine, G2Projective as G2};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
@@ -16,16 +19,25 @@ use proof_essentials::homomorphic_encryption::el_gamal::ElGamal;
 
 use proof_essentials::homomorphic_encryption::el_gamal;
 
+use betting::Betting;
+use hand::HandRank;
 use proof_essentials::vector_commitment::pedersen::PedersenCommitment;
 use proof_essentials::zkp::arguments::shuffle;
 use proof_essentials::zkp::proofs::{chaum_pedersen_dl_equality, schnorr_identification};
+use sc_poker_io::encoding;
+use sc_poker_io::lobby::{LobbyAction, TableStatus};
+use sc_poker_io::*;
+
+pub mod betting;
+pub mod hand;
+pub mod pot;
 // Instantiate concrete type for our card protocol
 type CardProtocol<'a> = discrete_log_cards::DLCards<'a, Curve>;
 
 type CardParameters = discrete_log_cards::Parameters<Curve>;
 
 // Choose elliptic curve setting
-type Curve = starknet_curve::Projective;
+pub type Curve = starknet_curve::Projective;
 type Scalar = starknet_curve::Fr;
 type Comm = PedersenCommitment<Curve>;
 
@@ -35,7 +47,7 @@ type ZKProofShuffle = shuffle::proof::Proof<Scalar, Enc, Comm>;
 
 type PublicKey = discrete_log_cards::PublicKey<Curve>;
 
-type Card = discrete_log_cards::Card<Curve>;
+pub type Card = discrete_log_cards::Card<Curve>;
 type MaskedCard = discrete_log_cards::MaskedCard<Curve>;
 type RevealToken = discrete_log_cards::RevealToken<Curve>;
 
@@ -43,208 +55,1655 @@ type ProofKeyOwnership = schnorr_identification::proof::Proof<Curve>;
 type RemaskingProof = chaum_pedersen_dl_equality::proof::Proof<Curve>;
 type RevealProof = chaum_pedersen_dl_equality::proof::Proof<Curve>;
 
-#[derive(PartialEq, Clone, Copy, Eq)]
-pub enum Suite {
-    Club,
-    Diamond,
-    Heart,
-    Spade,
-}
-
-impl Suite {
-    const VALUES: [Self; 4] = [Self::Club, Self::Diamond, Self::Heart, Self::Spade];
-}
-
-#[derive(PartialEq, PartialOrd, Clone, Copy, Eq)]
-pub enum Value {
-    Two,
-    Three,
-    Four,
-    Five,
-    Six,
-    Seven,
-    Eight,
-    Nine,
-    Ten,
-    Jack,
-    Queen,
-    King,
-    Ace,
-}
-
-impl Value {
-    const VALUES: [Self; 13] = [
-        Self::Two,
-        Self::Three,
-        Self::Four,
-        Self::Five,
-        Self::Six,
-        Self::Seven,
-        Self::Eight,
-        Self::Nine,
-        Self::Ten,
-        Self::Jack,
-        Self::Queen,
-        Self::King,
-        Self::Ace,
-    ];
-}
-
-#[derive(PartialEq, Clone, Eq, Copy)]
-pub struct ClassicPlayingCard {
-    value: Value,
-    suite: Suite,
-}
-
-impl ClassicPlayingCard {
-    pub fn new(value: Value, suite: Suite) -> Self {
-        Self { value, suite }
-    }
+/// A shuffled deck whose proof is being verified over several messages.
+struct PendingShuffle {
+    deck: Vec<MaskedCard>,
+    proof: ZKProofShuffle,
+    verifier: ShuffleVerifier<Curve>,
 }
 
 #[derive(Clone)]
 struct Player {
+    id: ActorId,
     name: String,
     pk: PublicKey,
     proof_key: ProofKeyOwnership,
-    cards: Vec<MaskedCard>,
-    opened_cards: Vec<Option<ClassicPlayingCard>>,
+    /// Deck positions of the player's hole cards.
+    cards: Vec<usize>,
+    has_shuffled: bool,
+    /// Chips between hands. During a hand the stack is tracked by `Game::betting`.
+    stack: u128,
+    /// Collateral slashed if the player sends an invalid proof.
+    deposit: u128,
 }
 
 impl Player {
-    pub fn new(pk: PublicKey, proof_key: ProofKeyOwnership, name: String) -> anyhow::Result<Self> {
-        Ok(Self {
-            name: name.clone(),
+    pub fn new(
+        id: ActorId,
+        pk: PublicKey,
+        proof_key: ProofKeyOwnership,
+        name: String,
+        stack: u128,
+        deposit: u128,
+    ) -> Self {
+        Self {
+            id,
+            name,
             pk,
             proof_key,
             cards: vec![],
-            opened_cards: vec![],
-        })
+            has_shuffled: false,
+            stack,
+            deposit,
+        }
+    }
+
+    /// The public information the key ownership proof is bound to: the raw bytes of the
+    /// player's `ActorId`. Clients must pass the same bytes to `prove_key_ownership`.
+    fn public_info(&self) -> [u8; 32] {
+        let mut info = [0u8; 32];
+        info.copy_from_slice(self.id.as_ref());
+        info
     }
 }
 
 pub struct Game {
+    id: GameId,
     parameters: CardParameters,
-    joint_pk: PublicKey,
+    /// Open cards in canonical order, see `encoding::encode_deck`.
+    card_plaintexts: Vec<Card>,
+    /// Aggregate of all the players' keys, computed when registration closes.
+    joint_pk: Option<PublicKey>,
     players: Vec<Player>,
     deck: Vec<MaskedCard>,
+    max_players: u8,
+    phase: Phase,
+    /// Seat of the player whose shuffle is expected next.
+    next_shuffler: usize,
+    /// The shuffle of `next_shuffler` while its proof is being verified.
+    pending_shuffle: Option<PendingShuffle>,
+    /// Deck positions reserved for the flop, the turn and the river, in that order.
+    community_cards: Vec<usize>,
+    /// Verified reveal tokens by deck position and by the player who issued them.
+    reveal_tokens: BTreeMap<usize, BTreeMap<ActorId, (RevealToken, RevealProof)>>,
+    /// Deck positions opened so far.
+    revealed_cards: BTreeMap<usize, ClassicPlayingCard>,
+    small_blind: u128,
+    big_blind: u128,
+    min_buy_in: u128,
+    max_buy_in: u128,
+    deposit: u128,
+    turn_timeout: u32,
+    /// Number of the latest scheduled `Timeout`, earlier ones are outdated.
+    timer: u64,
+    /// Block height at which the latest `Timeout` fires.
+    deadline: Option<u32>,
+    /// Players caught with an invalid proof and the amount taken from them.
+    slashed: Vec<(ActorId, u128)>,
+    /// Chips paid out to players and not withdrawn yet.
+    balances: BTreeMap<ActorId, u128>,
+    /// Seat of the dealer button.
+    button: usize,
+    /// Bets of the current hand, set up when the cards are dealt.
+    betting: Option<Betting>,
+    /// Lobby program that deployed the table, if any.
+    lobby: Option<ActorId>,
+    /// Phase and seat count last reported to `lobby`.
+    reported: Option<(Phase, u8)>,
+    /// Number of the current hand.
+    hand: u32,
+    /// Append-only log of everything the table has accepted.
+    history: Vec<HistoryEntry>,
 }
 
 impl Game {
-    fn add_player(&mut self, name: String, pk: Vec<u8>, proof_key: Vec<u8>) {
-        // let pub_key: PublicKey = starknet_curve::Projective::deserialize_uncompressed(&*pk).unwrap().into();
-        // let key_ownership =
-        //     schnorr_identification::proof::Proof::<starknet_curve::Projective>::deserialize_uncompressed(&*proof_key)
-        //         .unwrap();
+    fn new(id: GameId, init_msg: InitGame) -> Result<Self, GameError> {
+        let enc_parameters =
+            el_gamal::Parameters::<Curve>::deserialize_uncompressed(&*init_msg.enc_parameters)
+                .map_err(|_| GameError::MalformedParameters)?;
+        let commit_parameters =
+            CommitKey::<Curve>::deserialize_uncompressed(&*init_msg.commit_parameters)
+                .map_err(|_| GameError::MalformedParameters)?;
+        let generator =
+            el_gamal::Generator::<Curve>::deserialize_uncompressed(&*init_msg.generator)
+                .map_err(|_| GameError::MalformedParameters)?;
 
-        // let player = Player::new(pub_key, key_ownership, name).unwrap();
-        // self.players.push(player);
+        if !(2..=MAX_PLAYERS).contains(&init_msg.max_players) {
+            return Err(GameError::InvalidSeatCount);
+        }
+        if init_msg.small_blind == 0
+            || init_msg.small_blind > init_msg.big_blind
+            || init_msg.big_blind > init_msg.min_buy_in
+        {
+            return Err(GameError::InvalidBlinds);
+        }
+        if init_msg.min_buy_in > init_msg.max_buy_in {
+            return Err(GameError::InvalidBuyInLimits);
+        }
+        if init_msg.turn_timeout == 0 {
+            return Err(GameError::InvalidTimeout);
+        }
+
+        let parameters = discrete_log_cards::Parameters::new(
+            2,
+            26,
+            enc_parameters,
+            commit_parameters,
+            generator,
+        );
+
+        Ok(Self {
+            id,
+            parameters,
+            card_plaintexts: encoding::encode_deck(),
+            joint_pk: None,
+            players: Vec::new(),
+            deck: Vec::new(),
+            max_players: init_msg.max_players,
+            phase: Phase::Registration,
+            next_shuffler: 0,
+            pending_shuffle: None,
+            community_cards: Vec::new(),
+            reveal_tokens: BTreeMap::new(),
+            revealed_cards: BTreeMap::new(),
+            small_blind: init_msg.small_blind,
+            big_blind: init_msg.big_blind,
+            min_buy_in: init_msg.min_buy_in,
+            max_buy_in: init_msg.max_buy_in,
+            deposit: init_msg.deposit,
+            turn_timeout: init_msg.turn_timeout,
+            timer: 0,
+            deadline: None,
+            slashed: Vec::new(),
+            balances: BTreeMap::new(),
+            button: 0,
+            betting: None,
+            lobby: init_msg.lobby,
+            reported: None,
+            hand: 0,
+            history: Vec::new(),
+        })
     }
 
-    fn shuffle(&mut self, deck: Vec<Vec<u8>>, shuffle_proof: Vec<u8>) {
-        let mut shuffled_deck: Vec<MaskedCard> = Vec::new();
-        for card in deck.iter() {
-            let dec_card = MaskedCard::deserialize_uncompressed(&**card).unwrap();
-            shuffled_deck.push(dec_card);
+    fn add_player(
+        &mut self,
+        name: String,
+        pk: Vec<u8>,
+        proof_key: Vec<u8>,
+    ) -> Result<GameEvent, GameError> {
+        let player_id = msg::source();
+
+        if self.players.len() >= self.max_players as usize {
+            return Err(GameError::TableIsFull);
+        }
+        if self.players.iter().any(|player| player.id == player_id) {
+            return Err(GameError::AlreadyRegistered);
+        }
+
+        let buy_in = msg::value()
+            .checked_sub(self.deposit)
+            .ok_or(GameError::InvalidBuyIn)?;
+        if !(self.min_buy_in..=self.max_buy_in).contains(&buy_in) {
+            return Err(GameError::InvalidBuyIn);
+        }
+
+        let pub_key =
+            PublicKey::deserialize_uncompressed(&*pk).map_err(|_| GameError::InvalidPublicKey)?;
+        if self.players.iter().any(|player| player.pk == pub_key) {
+            return Err(GameError::PublicKeyAlreadyUsed);
         }
 
-        let dec_shuffle_proof = ZKProofShuffle::deserialize_uncompressed(&*shuffle_proof).unwrap();
+        let key_ownership = ProofKeyOwnership::deserialize_uncompressed(&*proof_key)
+            .map_err(|_| GameError::InvalidKeyOwnershipProof)?;
 
-        CardProtocol::verify_shuffle(
+        let mut player = Player::new(
+            player_id,
+            pub_key,
+            key_ownership,
+            name,
+            buy_in,
+            self.deposit,
+        );
+        let verification = CardProtocol::verify_key_ownership(
             &self.parameters,
-            &self.joint_pk,
-            &self.deck,
-            &shuffled_deck,
-            &dec_shuffle_proof,
+            &player.pk,
+            &player.public_info(),
+            &player.proof_key,
+        );
+        if verification.is_err() {
+            // The player is not seated: the deposit goes to the table and the buy-in back.
+            let amount = compensate(&mut self.players, player_id, player.deposit);
+            player.deposit -= amount;
+            self.slashed.push((player_id, amount));
+            pay_out(&mut self.balances, &mut player);
+            self.record(HandRecord::PlayerSlashed {
+                player: player_id,
+                reason: GameError::InvalidKeyOwnershipProof,
+                amount,
+            });
+            return Ok(GameEvent::PlayerSlashed {
+                player: player_id,
+                reason: GameError::InvalidKeyOwnershipProof,
+                amount,
+            });
+        }
+
+        self.record(HandRecord::PlayerJoined {
+            player: player_id,
+            seat: self.players.len() as u8,
+            pk: digest([serialize(&player.pk), serialize(&player.proof_key)]),
+            buy_in,
+        });
+        self.players.push(player);
+        self.seats_changed();
+
+        // The player is seated from here on: if closing the full table fails, the join
+        // still succeeds and `CloseRegistration` retries from `KeyAggregation`.
+        if self.players.len() == self.max_players as usize {
+            if let Ok(event) = self.close_registration() {
+                return Ok(event);
+            }
+        }
+
+        Ok(GameEvent::PlayerAdded {
+            player: player_id,
+            seat: (self.players.len() - 1) as u8,
+        })
+    }
+
+    fn close_registration(&mut self) -> Result<GameEvent, GameError> {
+        if !self.players.iter().any(|player| player.id == msg::source()) {
+            return Err(GameError::NotRegistered);
+        }
+        if self.players.len() < 2 {
+            return Err(GameError::NotEnoughPlayers);
+        }
+
+        self.phase = Phase::KeyAggregation;
+        self.aggregate_keys()
+    }
+
+    /// Computes the joint public key over all seated players, unless the seats are the same
+    /// as in the previous hand, and the initial deck masked under it. On failure the table
+    /// stays in `KeyAggregation` and the step can be retried.
+    fn aggregate_keys(&mut self) -> Result<GameEvent, GameError> {
+        let joint_pk = match self.joint_pk {
+            Some(joint_pk) => joint_pk,
+            None => {
+                let key_proof_info = self
+                    .players
+                    .iter()
+                    .map(|player| (player.pk, player.proof_key, player.public_info()))
+                    .collect::<Vec<_>>();
+                CardProtocol::compute_aggregate_key(&self.parameters, &key_proof_info)?
+            }
+        };
+        self.deck = self.initial_deck(&joint_pk)?;
+        self.joint_pk = Some(joint_pk);
+        self.phase = Phase::Shuffling;
+        self.record(HandRecord::KeysAggregated {
+            joint_pk: digest([serialize(&joint_pk)]),
+            initial_deck: digest(self.deck.iter().map(serialize)),
+        });
+
+        Ok(GameEvent::RegistrationClosed {
+            joint_pk: serialize(&joint_pk),
+        })
+    }
+
+    /// Assigns deck positions like a dealer going around the table: the first card to
+    /// every seat, then the second one, followed by the five community cards. The blinds
+    /// are posted, the pre-flop betting round starts once the hole cards can be opened.
+    fn deal(&mut self) -> Result<GameEvent, GameError> {
+        if !self.players.iter().any(|player| player.id == msg::source()) {
+            return Err(GameError::NotRegistered);
+        }
+
+        self.deal_cards()
+    }
+
+    fn deal_cards(&mut self) -> Result<GameEvent, GameError> {
+        let num_of_players = self.players.len();
+        for (seat, player) in self.players.iter_mut().enumerate() {
+            player.cards = (0..HOLE_CARDS)
+                .map(|round| round * num_of_players + seat)
+                .collect();
+        }
+
+        let first_community_card = HOLE_CARDS * num_of_players;
+        self.community_cards =
+            (first_community_card..first_community_card + COMMUNITY_CARDS).collect();
+        self.phase = Phase::PreFlop;
+
+        let stacks = self
+            .players
+            .iter()
+            .map(|player| player.stack)
+            .collect::<Vec<_>>();
+        // Betting opens once the tokens for the hole cards are in, see `hole_cards_revealable`.
+        self.betting = Some(Betting::new(
+            &stacks,
+            self.button,
+            self.small_blind,
+            self.big_blind,
+        ));
+
+        let hole_cards = self
+            .players
+            .iter()
+            .map(|player| (player.id, positions(&player.cards)))
+            .collect::<Vec<_>>();
+        let community_cards = positions(&self.community_cards);
+        self.record(HandRecord::CardsDealt {
+            button: self.button as u8,
+            hole_cards: hole_cards.clone(),
+            community_cards: community_cards.clone(),
+        });
+
+        Ok(GameEvent::CardsDealt {
+            hole_cards,
+            community_cards,
+        })
+    }
+
+    /// Accepts a player's share for opening the card at `card_index`. Hole cards can only
+    /// be opened by their owner at showdown, so owners may not submit tokens for them earlier,
+    /// while everybody else owes them before the pre-flop betting opens.
+    fn submit_reveal_token(
+        &mut self,
+        card_index: u8,
+        token: Vec<u8>,
+        proof: Vec<u8>,
+    ) -> Result<GameEvent, GameError> {
+        let player_id = msg::source();
+        let seat = self
+            .players
+            .iter()
+            .position(|player| player.id == player_id)
+            .ok_or(GameError::NotRegistered)?;
+        let player = &self.players[seat];
+
+        let card_index = card_index as usize;
+        let is_own_card = player.cards.contains(&card_index);
+        let is_dealt = self.community_cards.contains(&card_index)
+            || self
+                .players
+                .iter()
+                .any(|player| player.cards.contains(&card_index));
+        if !is_dealt {
+            return Err(GameError::InvalidCardIndex);
+        }
+        if is_own_card && self.phase != Phase::Showdown {
+            return Err(GameError::OwnCardBeforeShowdown);
+        }
+        if self.community_cards.contains(&card_index) && !self.street_cards().contains(&card_index)
+        {
+            return Err(GameError::StreetNotOpen);
+        }
+        if self.has_token(card_index, player_id) {
+            return Err(GameError::RevealTokenAlreadySubmitted);
+        }
+
+        let token = RevealToken::deserialize_uncompressed(&*token)
+            .map_err(|_| GameError::MalformedRevealToken)?;
+        let proof = RevealProof::deserialize_uncompressed(&*proof)
+            .map_err(|_| GameError::MalformedProof)?;
+
+        let verification = CardProtocol::verify_reveal(
+            &self.parameters,
+            &player.pk,
+            &token,
+            &self.deck[card_index],
+            &proof,
+        );
+        if verification.is_err() {
+            return Ok(self.slash(seat, GameError::InvalidRevealProof));
+        }
+
+        self.record(HandRecord::RevealTokenAccepted {
+            player: player_id,
+            card_index: card_index as u8,
+            token: digest([serialize(&token), serialize(&proof)]),
+        });
+        self.reveal_tokens
+            .entry(card_index)
+            .or_default()
+            .insert(player_id, (token, proof));
+
+        if matches!(self.phase, Phase::Flop | Phase::Turn | Phase::River)
+            && !self.is_street_open()
+            && self.street_revealable()
+        {
+            return self.open_street();
+        }
+        // The blinds may have put everybody but one player all-in, then nobody bets.
+        let betting_closed = self
+            .betting
+            .as_ref()
+            .is_some_and(|betting| betting.to_act.is_none());
+        if self.phase == Phase::PreFlop && betting_closed && self.hole_cards_revealable() {
+            self.end_betting_round()?;
+        }
+        if self.phase == Phase::Showdown
+            && self
+                .showdown_seats()
+                .into_iter()
+                .all(|seat| self.can_show(seat))
+        {
+            return self.showdown(&[]);
+        }
+
+        Ok(GameEvent::RevealTokenAccepted {
+            player: player_id,
+            card_index: card_index as u8,
+        })
+    }
+
+    /// Accepts a folded player's tokens for community cards that are not open yet. They
+    /// count once the street of their card comes, and the current street opens if they were
+    /// the last tokens it was waiting for.
+    fn precommit_reveal_tokens(
+        &mut self,
+        tokens: Vec<RevealTokenShare>,
+    ) -> Result<GameEvent, GameError> {
+        let player_id = msg::source();
+        let seat = self
+            .players
+            .iter()
+            .position(|player| player.id == player_id)
+            .ok_or(GameError::NotRegistered)?;
+        let folded = self
+            .betting
+            .as_ref()
+            .is_some_and(|betting| betting.seats[seat].folded);
+        if !folded {
+            return Err(GameError::StillInHand);
+        }
+        if tokens.is_empty() {
+            return Err(GameError::NoRevealTokens);
+        }
+
+        let shares = self.token_shares(player_id, tokens)?;
+        if !self.verify_shares(seat, &shares) {
+            return Ok(self.slash(seat, GameError::InvalidRevealProof));
+        }
+
+        let card_indices = shares
+            .iter()
+            .map(|&(card_index, _, _)| card_index as u8)
+            .collect();
+        if let Some(opened) = self.precommit(player_id, shares)? {
+            return Ok(opened);
+        }
+
+        Ok(GameEvent::RevealTokensPrecommitted {
+            player: player_id,
+            card_indices,
+        })
+    }
+
+    /// Folds the sender's hand and commits `tokens` for community cards in the same step,
+    /// so the rest of the board can be opened without them.
+    fn fold(&mut self, tokens: Vec<RevealTokenShare>) -> Result<GameEvent, GameError> {
+        let player_id = msg::source();
+        let seat = self
+            .players
+            .iter()
+            .position(|player| player.id == player_id)
+            .ok_or(GameError::NotRegistered)?;
+        if self.phase == Phase::PreFlop && !self.hole_cards_revealable() {
+            return Err(GameError::WaitingForRevealTokens);
+        }
+
+        let shares = self.token_shares(player_id, tokens)?;
+        if !self.verify_shares(seat, &shares) {
+            return Ok(self.slash(seat, GameError::InvalidRevealProof));
+        }
+
+        let event = self.act(seat, betting::Action::Fold)?;
+        // Once the hand is over its cards are never opened.
+        if self.betting.is_some() {
+            self.precommit(player_id, shares)?;
+        }
+        Ok(event)
+    }
+
+    /// Deserializes the shares of `player_id` for community cards that are not open yet,
+    /// rejecting them all if any cannot be accepted.
+    fn token_shares(
+        &self,
+        player_id: ActorId,
+        tokens: Vec<RevealTokenShare>,
+    ) -> Result<Vec<(usize, RevealToken, RevealProof)>, GameError> {
+        let mut shares: Vec<(usize, RevealToken, RevealProof)> = Vec::new();
+        for share in tokens {
+            let card_index = share.card_index as usize;
+            if !self.community_cards.contains(&card_index) {
+                return Err(GameError::InvalidCardIndex);
+            }
+            if self.revealed_cards.contains_key(&card_index) {
+                return Err(GameError::CardAlreadyOpen);
+            }
+            if self.has_token(card_index, player_id)
+                || shares.iter().any(|(index, _, _)| *index == card_index)
+            {
+                return Err(GameError::RevealTokenAlreadySubmitted);
+            }
+
+            let token = RevealToken::deserialize_uncompressed(&*share.token)
+                .map_err(|_| GameError::MalformedRevealToken)?;
+            let proof = RevealProof::deserialize_uncompressed(&*share.proof)
+                .map_err(|_| GameError::MalformedProof)?;
+            shares.push((card_index, token, proof));
+        }
+        Ok(shares)
+    }
+
+    fn verify_shares(&self, seat: usize, shares: &[(usize, RevealToken, RevealProof)]) -> bool {
+        let player = &self.players[seat];
+        shares.iter().all(|(card_index, token, proof)| {
+            CardProtocol::verify_reveal(
+                &self.parameters,
+                &player.pk,
+                token,
+                &self.deck[*card_index],
+                proof,
+            )
+            .is_ok()
+        })
+    }
+
+    /// Stores verified shares of `player_id` and opens the current street if they were the
+    /// last tokens it was waiting for.
+    fn precommit(
+        &mut self,
+        player_id: ActorId,
+        shares: Vec<(usize, RevealToken, RevealProof)>,
+    ) -> Result<Option<GameEvent>, GameError> {
+        for (card_index, token, proof) in shares {
+            self.record(HandRecord::RevealTokenAccepted {
+                player: player_id,
+                card_index: card_index as u8,
+                token: digest([serialize(&token), serialize(&proof)]),
+            });
+            self.reveal_tokens
+                .entry(card_index)
+                .or_default()
+                .insert(player_id, (token, proof));
+        }
+
+        if matches!(self.phase, Phase::Flop | Phase::Turn | Phase::River)
+            && !self.is_street_open()
+            && self.street_revealable()
+        {
+            return self.open_street().map(Some);
+        }
+        Ok(None)
+    }
+
+    /// Deck positions of the community cards dealt on the current street.
+    fn street_cards(&self) -> &[usize] {
+        let range = match self.phase {
+            Phase::Flop => 0..3,
+            Phase::Turn => 3..4,
+            Phase::River => 4..5,
+            _ => 0..0,
+        };
+        self.community_cards.get(range).unwrap_or_default()
+    }
+
+    fn has_token(&self, card_index: usize, player_id: ActorId) -> bool {
+        self.reveal_tokens
+            .get(&card_index)
+            .is_some_and(|tokens| tokens.contains_key(&player_id))
+    }
+
+    fn has_all_tokens(&self, card_index: usize) -> bool {
+        let tokens = match self.reveal_tokens.get(&card_index) {
+            Some(tokens) => tokens,
+            None => return false,
+        };
+        self.players
+            .iter()
+            .all(|player| tokens.contains_key(&player.id))
+    }
+
+    fn street_revealable(&self) -> bool {
+        self.street_cards()
+            .iter()
+            .all(|&card_index| self.has_all_tokens(card_index))
+    }
+
+    fn is_street_open(&self) -> bool {
+        self.street_cards()
+            .iter()
+            .all(|card_index| self.revealed_cards.contains_key(card_index))
+    }
+
+    /// Combines the tokens of the current street, publishes its cards and starts the
+    /// street's betting round.
+    fn open_street(&mut self) -> Result<GameEvent, GameError> {
+        let mut opened = Vec::new();
+        for &card_index in self.street_cards() {
+            opened.push((card_index, self.unmask_card(card_index)?));
+        }
+
+        self.revealed_cards.extend(opened.iter().copied());
+        let cards = opened
+            .into_iter()
+            .map(|(card_index, card)| (card_index as u8, card))
+            .collect::<Vec<_>>();
+        self.record(HandRecord::CardsOpened {
+            cards: cards.clone(),
+        });
+
+        let betting = self.betting.as_mut().ok_or(GameError::WrongPhase)?;
+        betting.next_street();
+        // Nobody bets any more once all but one of the remaining players are all-in.
+        if betting.to_act.is_none() {
+            self.end_betting_round()?;
+        }
+
+        Ok(GameEvent::CommunityCardsOpened { cards })
+    }
+
+    /// Combines the reveal tokens of every player for the card at `card_index`.
+    fn unmask_card(&self, card_index: usize) -> Result<ClassicPlayingCard, GameError> {
+        let decryption_key = self.reveal_tokens[&card_index]
+            .iter()
+            .map(|(player_id, (token, proof))| {
+                let player = self
+                    .players
+                    .iter()
+                    .find(|player| player.id == *player_id)
+                    .expect("Tokens are only accepted from seated players");
+                (*token, *proof, player.pk)
+            })
+            .collect::<Vec<_>>();
+
+        let card = CardProtocol::unmask(&self.parameters, &decryption_key, &self.deck[card_index])?;
+        self.classic_card(&card)
+    }
+
+    /// Seats still in the hand, which have a claim on the pots at showdown.
+    fn showdown_seats(&self) -> Vec<usize> {
+        let betting = match &self.betting {
+            Some(betting) => betting,
+            None => return Vec::new(),
+        };
+        (0..self.players.len())
+            .filter(|&seat| !betting.seats[seat].folded)
+            .collect()
+    }
+
+    fn can_show(&self, seat: usize) -> bool {
+        self.players[seat]
+            .cards
+            .iter()
+            .all(|&card_index| self.has_all_tokens(card_index))
+    }
+
+    /// Settles the showdown and ends the hand. The players at `leaving` lose their seat.
+    fn showdown(&mut self, leaving: &[usize]) -> Result<GameEvent, GameError> {
+        let event = self.settle_showdown(leaving)?;
+        self.finish_hand(leaving);
+        Ok(event)
+    }
+
+    /// Opens the hole cards of every player still in the hand that can be opened, ranks them
+    /// against the board and pays the pots. The players at `leaving` forfeit what they put
+    /// in, like the offenders of `forfeit_hand`. Hands that cannot be opened have no claim
+    /// either, and if none can, the pots are shared by the players still in the hand. Nothing
+    /// changes if the showdown cannot be settled.
+    fn settle_showdown(&mut self, leaving: &[usize]) -> Result<GameEvent, GameError> {
+        let board = self
+            .community_cards
+            .iter()
+            .map(|card_index| self.revealed_cards.get(card_index).copied())
+            .collect::<Option<Vec<_>>>()
+            .ok_or(GameError::StreetNotOpen)?;
+
+        let mut ranks = vec![None; self.players.len()];
+        let mut shown = Vec::new();
+        for seat in self.showdown_seats() {
+            if leaving.contains(&seat) || !self.can_show(seat) {
+                continue;
+            }
+            let hole_cards = self.players[seat]
+                .cards
+                .iter()
+                .map(|&card_index| Ok((card_index, self.unmask_card(card_index)?)))
+                .collect::<Result<Vec<_>, GameError>>()?;
+            let cards = hole_cards
+                .iter()
+                .map(|&(_, card)| card)
+                .chain(board.iter().copied())
+                .collect::<Vec<_>>();
+            let rank = hand::evaluate(&cards);
+            ranks[seat] = Some(rank);
+            shown.push((seat, hole_cards, rank.category));
+        }
+
+        let mut betting = self.betting.take().ok_or(GameError::WrongPhase)?;
+        for &seat in leaving {
+            betting.forfeit(seat);
+        }
+        let winnings = settle_pots(&mut betting, &ranks);
+        for (player, seat) in self.players.iter_mut().zip(betting.seats) {
+            player.stack = seat.stack;
+        }
+
+        let mut hands = Vec::new();
+        for (seat, hole_cards, category) in shown {
+            self.revealed_cards.extend(hole_cards.iter().copied());
+            let cards = hole_cards
+                .into_iter()
+                .map(|(_, card)| card)
+                .collect::<Vec<_>>();
+            let player = self.players[seat].id;
+            self.record(HandRecord::HandShown {
+                player,
+                cards: cards.clone(),
+                category,
+            });
+            hands.push((player, cards, category));
+        }
+        let winnings = winnings
+            .into_iter()
+            .enumerate()
+            .filter(|&(_, amount)| amount > 0)
+            .map(|(seat, amount)| (self.players[seat].id, amount))
+            .collect::<Vec<_>>();
+        for &(player, amount) in &winnings {
+            self.record(HandRecord::PotCollected { player, amount });
+        }
+
+        Ok(GameEvent::ShowdownSettled { hands, winnings })
+    }
+
+    fn bet(&mut self, action: betting::Action) -> Result<GameEvent, GameError> {
+        let seat = self
+            .players
+            .iter()
+            .position(|player| player.id == msg::source())
+            .ok_or(GameError::NotRegistered)?;
+        if self.phase == Phase::PreFlop && !self.hole_cards_revealable() {
+            return Err(GameError::WaitingForRevealTokens);
+        }
+
+        self.act(seat, action)
+    }
+
+    /// Applies a betting action of `seat`. The table moves to the next street when the
+    /// round closes, and the hand ends when everybody else has folded.
+    fn act(&mut self, seat: usize, action: betting::Action) -> Result<GameEvent, GameError> {
+        let betting = self.betting.as_mut().ok_or(GameError::WrongPhase)?;
+        betting.act(seat, action)?;
+
+        let player = betting.seats[seat].clone();
+        let to_act = betting.to_act;
+        let last_player_standing = betting.last_player_standing();
+        self.record(HandRecord::PlayerActed {
+            player: self.players[seat].id,
+            action: action.into(),
+            bet: player.bet,
+            stack: player.stack,
+        });
+
+        if let Some(winner) = last_player_standing {
+            let amount = self
+                .betting
+                .as_mut()
+                .expect("The hand is under way")
+                .award_pot(winner);
+            let winner = self.players[winner].id;
+            self.record(HandRecord::PotCollected {
+                player: winner,
+                amount,
+            });
+            self.finish_hand(&[]);
+            return Ok(GameEvent::HandWon { winner, amount });
+        }
+
+        let next_to_act = to_act.map(|next| self.players[next].id);
+        if next_to_act.is_none() {
+            self.end_betting_round()?;
+        }
+
+        Ok(GameEvent::PlayerActed {
+            player: self.players[seat].id,
+            folded: player.folded,
+            bet: player.bet,
+            stack: player.stack,
+            next_to_act,
+        })
+    }
+
+    /// Moves on to the next street, or to the showdown after the river. A street whose
+    /// tokens have all been committed in advance opens right away.
+    fn end_betting_round(&mut self) -> Result<(), GameError> {
+        self.phase = match self.phase {
+            Phase::PreFlop => Phase::Flop,
+            Phase::Flop => Phase::Turn,
+            Phase::Turn => Phase::River,
+            _ => Phase::Showdown,
+        };
+
+        if self.phase != Phase::Showdown && self.street_revealable() {
+            self.open_street()?;
+        }
+        Ok(())
+    }
+
+    /// Handles the program's own `Timeout` message for the player, or players, the table
+    /// has been waiting for since the timer was armed.
+    fn timeout(&mut self, timer: u64) -> Result<GameEvent, GameError> {
+        if msg::source() != exec::program_id() {
+            return Err(GameError::NotAllowed);
+        }
+        if timer != self.timer {
+            return Err(GameError::OutdatedTimeout);
+        }
+
+        let to_act = self.betting.as_ref().and_then(|betting| betting.to_act);
+        let missing_tokens = self.missing_tokens();
+        let stalled = match (self.phase, to_act) {
+            (Phase::Dealing, _) => return self.deal_cards(),
+            (Phase::Shuffling, _) => vec![self.next_shuffler],
+            // Players holding back tokens are dealt with before anybody is folded.
+            (Phase::PreFlop | Phase::Flop | Phase::Turn | Phase::River, _)
+                if !missing_tokens.is_empty() =>
+            {
+                missing_tokens
+            }
+            (Phase::PreFlop | Phase::Flop | Phase::Turn | Phase::River, Some(seat)) => {
+                let players = vec![self.players[seat].id];
+                self.record(HandRecord::PlayersTimedOut {
+                    players: players.clone(),
+                });
+                self.act(seat, betting::Action::Fold)?;
+                return Ok(GameEvent::PlayersTimedOut { players });
+            }
+            (Phase::Showdown, _) => {
+                self.record(HandRecord::PlayersTimedOut {
+                    players: missing_tokens
+                        .iter()
+                        .map(|&seat| self.players[seat].id)
+                        .collect(),
+                });
+                return self.showdown(&missing_tokens);
+            }
+            _ => return Err(GameError::WrongPhase),
+        };
+
+        let players = stalled
+            .iter()
+            .map(|&seat| self.players[seat].id)
+            .collect::<Vec<_>>();
+        self.record(HandRecord::PlayersTimedOut {
+            players: players.clone(),
+        });
+
+        // Without the stalled players' keys no further card can be opened, so they also
+        // lose their seat.
+        self.forfeit_hand(&stalled);
+        self.finish_hand(&stalled);
+
+        Ok(GameEvent::PlayersTimedOut { players })
+    }
+
+    /// Whether every player has submitted the tokens for the other players' hole cards.
+    fn hole_cards_revealable(&self) -> bool {
+        self.phase == Phase::PreFlop && self.missing_tokens().is_empty()
+    }
+
+    /// Seats whose reveal tokens the table needs before it can go on: those for the others'
+    /// hole cards before the pre-flop betting, those for the current street while it is not
+    /// open, and those for the hole cards of the showdown seats.
+    fn missing_tokens(&self) -> Vec<usize> {
+        if self.phase == Phase::PreFlop {
+            return (0..self.players.len())
+                .filter(|&seat| {
+                    let player_id = self.players[seat].id;
+                    self.players
+                        .iter()
+                        .filter(|other| other.id != player_id)
+                        .flat_map(|other| &other.cards)
+                        .any(|&card_index| !self.has_token(card_index, player_id))
+                })
+                .collect();
+        }
+
+        let card_indices = match self.phase {
+            Phase::Flop | Phase::Turn | Phase::River if !self.is_street_open() => {
+                self.street_cards().to_vec()
+            }
+            Phase::Showdown => self
+                .showdown_seats()
+                .into_iter()
+                .flat_map(|seat| self.players[seat].cards.clone())
+                .collect(),
+            _ => Vec::new(),
+        };
+        (0..self.players.len())
+            .filter(|&seat| {
+                card_indices
+                    .iter()
+                    .any(|&card_index| !self.has_token(card_index, self.players[seat].id))
+            })
+            .collect()
+    }
+
+    /// What the table is waiting for. The deadline only moves when this changes, so
+    /// actions that do not move the table on cannot put it off.
+    fn waiting_for(&self) -> (u32, Phase, Option<ActorId>, Option<usize>, Vec<usize>) {
+        let verified_stages = self
+            .pending_shuffle
+            .as_ref()
+            .map(|pending| pending.verifier.verified_stages());
+        (
+            self.hand,
+            self.phase,
+            self.turn(),
+            verified_stages,
+            self.missing_tokens(),
         )
-        .unwrap();
+    }
 
-        self.deck = shuffled_deck;
+    /// Arms a new deadline if the table is waiting for a player, which outdates any
+    /// `Timeout` scheduled before.
+    fn start_timer(&mut self) {
+        self.timer += 1;
+        self.deadline = None;
+        if !matches!(
+            self.phase,
+            Phase::Shuffling
+                | Phase::Dealing
+                | Phase::PreFlop
+                | Phase::Flop
+                | Phase::Turn
+                | Phase::River
+                | Phase::Showdown
+        ) {
+            return;
+        }
+
+        let timeout = GameMessage::Action {
+            game_id: self.id,
+            action: GameAction::Timeout { timer: self.timer },
+        };
+        msg::send_delayed(exec::program_id(), timeout, 0, self.turn_timeout)
+            .expect("Unable to schedule the timeout");
+        self.deadline = Some(exec::block_height() + self.turn_timeout);
     }
-}
 
-#[derive(Encode, Decode)]
-pub struct InitGame {
-    pub enc_parameters: Vec<u8>,
-    pub commit_parameters: Vec<u8>,
-    pub generator: Vec<u8>,
-   
-}
+    /// Appends `record` to the history of the current hand.
+    fn record(&mut self, record: HandRecord) {
+        self.history.push(HistoryEntry {
+            hand: self.hand,
+            block: exec::block_height(),
+            record,
+        });
+    }
 
-#[derive(Encode, Decode)]
-pub enum GameAction {
-    AddPlayer {
-        name: String,
-        pub_key: Vec<u8>,
-        proof_key_ownership: Vec<u8>,
-    },
-    Shuffle {
+    /// Lets the lobby know when the phase or the number of players has changed, so it can
+    /// list the table while seats are free.
+    fn report_to_lobby(&mut self) {
+        let lobby = match self.lobby {
+            Some(lobby) => lobby,
+            None => return,
+        };
+        let status = (self.phase, self.players.len() as u8);
+        if self.reported == Some(status) {
+            return;
+        }
+
+        let update = TableStatus {
+            game_id: self.id,
+            phase: status.0,
+            seats_taken: status.1,
+        };
+        msg::send(lobby, LobbyAction::TableUpdated(update), 0)
+            .expect("Unable to report to the lobby");
+        self.reported = Some(status);
+    }
+
+    /// Ends a hand that cannot go on. `offenders` lose what they put in, the pots are
+    /// shared by everybody else still in the hand.
+    fn forfeit_hand(&mut self, offenders: &[usize]) {
+        let mut betting = match self.betting.take() {
+            Some(betting) => betting,
+            None => return,
+        };
+
+        for &seat in offenders {
+            betting.forfeit(seat);
+        }
+        let no_claims = vec![None; betting.seats.len()];
+        let winnings = settle_pots(&mut betting, &no_claims);
+
+        for (player, seat) in self.players.iter_mut().zip(betting.seats) {
+            player.stack = seat.stack;
+        }
+        for (seat, amount) in winnings.into_iter().enumerate() {
+            if amount > 0 {
+                self.record(HandRecord::PotCollected {
+                    player: self.players[seat].id,
+                    amount,
+                });
+            }
+        }
+    }
+
+    /// Punishes a provably invalid proof from `seat`: the hand is forfeited, the offender's
+    /// deposit is shared by the other players and the offender loses their seat. At showdown
+    /// the hands that can be opened are still ranked for the pots.
+    fn slash(&mut self, seat: usize, reason: GameError) -> GameEvent {
+        let settled = self.phase == Phase::Showdown && self.settle_showdown(&[seat]).is_ok();
+        if !settled {
+            self.forfeit_hand(&[seat]);
+        }
+
+        let offender = &mut self.players[seat];
+        let player = offender.id;
+        let deposit = mem::take(&mut offender.deposit);
+        let amount = compensate(&mut self.players, player, deposit);
+        self.players[seat].deposit = deposit - amount;
+        self.slashed.push((player, amount));
+        self.record(HandRecord::PlayerSlashed {
+            player,
+            reason: reason.clone(),
+            amount,
+        });
+
+        self.finish_hand(&[seat]);
+        GameEvent::PlayerSlashed {
+            player,
+            reason,
+            amount,
+        }
+    }
+
+    /// Carries the stacks over from the hand's bets and reopens the table for registration
+    /// until the next hand. The players at `leaving` and those without chips are paid out
+    /// and lose their seat. The session ends, paying everybody, once fewer than two players
+    /// are left.
+    fn finish_hand(&mut self, leaving: &[usize]) {
+        let dealt = !self.community_cards.is_empty();
+        if let Some(betting) = self.betting.take() {
+            for (player, seat) in self.players.iter_mut().zip(betting.seats) {
+                player.stack = seat.stack;
+            }
+        }
+
+        let stays = self
+            .players
+            .iter()
+            .enumerate()
+            .map(|(seat, player)| player.stack > 0 && !leaving.contains(&seat))
+            .collect::<Vec<_>>();
+        if stays.iter().filter(|&&stays| stays).count() < 2 {
+            self.phase = Phase::Finished;
+            for seat in 0..self.players.len() {
+                self.release(seat);
+            }
+            return;
+        }
+
+        // Once the cards were dealt the button moves to the next player who stays, and the
+        // blinds follow it. A hand aborted before that keeps the button where it was, or
+        // passes it on if its player leaves.
+        let num_of_seats = stays.len();
+        let first_offset = usize::from(dealt);
+        let button = (first_offset..first_offset + num_of_seats)
+            .map(|offset| (self.button + offset) % num_of_seats)
+            .find(|&seat| stays[seat])
+            .expect("At least two players stay");
+        self.button = stays[..button].iter().filter(|&&stays| stays).count();
+
+        for (seat, &stays) in stays.iter().enumerate() {
+            if !stays {
+                self.release(seat);
+            }
+        }
+        let mut stays = stays.into_iter();
+        self.players.retain(|_| stays.next().unwrap_or_default());
+        if self.players.len() < num_of_seats {
+            self.seats_changed();
+        }
+
+        // Players may join or leave before `CloseRegistration` starts the next hand, which
+        // begins from the initial deck again and is shuffled by everybody.
+        self.hand += 1;
+        for player in self.players.iter_mut() {
+            player.cards.clear();
+            player.has_shuffled = false;
+        }
+        self.next_shuffler = 0;
+        self.pending_shuffle = None;
+        self.community_cards.clear();
+        self.reveal_tokens.clear();
+        self.revealed_cards.clear();
+        self.phase = Phase::Registration;
+    }
+
+    /// The joint key no longer matches the seats: it is computed again when the next hand
+    /// starts, and the deck masked under the old one is dropped.
+    fn seats_changed(&mut self) {
+        self.joint_pk = None;
+        self.deck.clear();
+    }
+
+    /// Credits the player at `seat` their stack and deposit.
+    fn release(&mut self, seat: usize) {
+        let player = &mut self.players[seat];
+        let player_id = player.id;
+        let amount = pay_out(&mut self.balances, player);
+        if amount > 0 {
+            self.record(HandRecord::PaidOut {
+                player: player_id,
+                amount,
+            });
+        }
+    }
+
+    /// Removes the sender from the table and credits their stack and deposit to their
+    /// balance.
+    fn leave_table(&mut self) -> Result<GameEvent, GameError> {
+        let player_id = msg::source();
+        let seat = self
+            .players
+            .iter()
+            .position(|player| player.id == player_id)
+            .ok_or(GameError::NotRegistered)?;
+
+        let mut player = self.players.remove(seat);
+        let amount = pay_out(&mut self.balances, &mut player);
+        self.record(HandRecord::PlayerLeft {
+            player: player_id,
+            amount,
+        });
+        // The button stays with the same player, or passes to the next seat if it leaves.
+        if seat < self.button {
+            self.button -= 1;
+        }
+        if self.button >= self.players.len() {
+            self.button = 0;
+        }
+        self.seats_changed();
+
+        Ok(GameEvent::CashedOut {
+            player: player_id,
+            amount,
+        })
+    }
+
+    /// Sends the sender's balance to them. If the transfer cannot be made the balance is
+    /// kept, so nothing paid out is ever lost.
+    fn withdraw(&mut self) -> Result<GameEvent, GameError> {
+        let player_id = msg::source();
+        let amount = self
+            .balances
+            .remove(&player_id)
+            .ok_or(GameError::NothingToWithdraw)?;
+
+        let event = GameEvent::Withdrawn {
+            player: player_id,
+            amount,
+        };
+        if msg::send(player_id, event.clone(), amount).is_err() {
+            self.balances.insert(player_id, amount);
+            return Err(GameError::TransferFailed);
+        }
+        Ok(event)
+    }
+
+    /// Maps an unmasked plaintext back to the playing card it encodes.
+    fn classic_card(&self, card: &Card) -> Result<ClassicPlayingCard, GameError> {
+        encoding::decode_card(&self.card_plaintexts, card).ok_or(GameError::UnknownCard)
+    }
+
+    /// Checks that `action` is accepted in the current phase.
+    fn check_phase(&self, action: &GameAction) -> Result<(), GameError> {
+        let allowed = match action {
+            GameAction::JoinTable { .. } => self.phase == Phase::Registration,
+            GameAction::CloseRegistration => {
+                matches!(self.phase, Phase::Registration | Phase::KeyAggregation)
+            }
+            GameAction::Shuffle { .. } | GameAction::VerifyShuffle => {
+                self.phase == Phase::Shuffling
+            }
+            GameAction::Deal => self.phase == Phase::Dealing,
+            GameAction::SubmitRevealToken { .. } => matches!(
+                self.phase,
+                Phase::PreFlop | Phase::Flop | Phase::Turn | Phase::River | Phase::Showdown
+            ),
+            GameAction::LeaveTable => self.phase == Phase::Registration,
+            GameAction::Withdraw | GameAction::Timeout { .. } => true,
+            GameAction::PrecommitRevealTokens { .. }
+            | GameAction::Check
+            | GameAction::Call
+            | GameAction::Bet { .. }
+            | GameAction::Raise { .. }
+            | GameAction::Fold { .. } => matches!(
+                self.phase,
+                Phase::PreFlop | Phase::Flop | Phase::Turn | Phase::River
+            ),
+        };
+
+        if allowed {
+            Ok(())
+        } else {
+            Err(GameError::WrongPhase)
+        }
+    }
+
+    /// The player the table is waiting for.
+    fn turn(&self) -> Option<ActorId> {
+        match self.phase {
+            Phase::Shuffling => self.players.get(self.next_shuffler).map(|player| player.id),
+            Phase::PreFlop if !self.hole_cards_revealable() => None,
+            Phase::PreFlop | Phase::Flop | Phase::Turn | Phase::River => self
+                .betting
+                .as_ref()
+                .and_then(|betting| betting.to_act)
+                .map(|seat| self.players[seat].id),
+            _ => None,
+        }
+    }
+
+    /// Masks the agreed card plaintexts under the joint key with the public factor one,
+    /// so that anyone can recompute the deck the first shuffle starts from.
+    fn initial_deck(&self, joint_pk: &PublicKey) -> Result<Vec<MaskedCard>, GameError> {
+        self.card_plaintexts
+            .iter()
+            .map(|card| card.mask(&self.parameters.enc_parameters, joint_pk, &Scalar::one()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(GameError::from)
+    }
+
+    fn shuffle(
+        &mut self,
         deck: Vec<Vec<u8>>,
         shuffle_proof: Vec<u8>,
-    },
+    ) -> Result<GameEvent, GameError> {
+        let player_id = msg::source();
+        if self.players[self.next_shuffler].id != player_id {
+            return Err(GameError::NotYourTurn);
+        }
+
+        if self.pending_shuffle.is_some() {
+            return Err(GameError::ShuffleVerificationPending);
+        }
+
+        let shuffled_deck = deck
+            .iter()
+            .map(|card| MaskedCard::deserialize_uncompressed(&**card))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| GameError::MalformedDeck)?;
+
+        let dec_shuffle_proof = ZKProofShuffle::deserialize_uncompressed(&*shuffle_proof)
+            .map_err(|_| GameError::MalformedProof)?;
+
+        self.pending_shuffle = Some(PendingShuffle {
+            deck: shuffled_deck,
+            proof: dec_shuffle_proof,
+            verifier: ShuffleVerifier::new()?,
+        });
+
+        self.verify_shuffle_stage()
+    }
+
+    /// Continues the verification of the pending shuffle on behalf of the shuffler.
+    fn verify_shuffle(&mut self) -> Result<GameEvent, GameError> {
+        if !self.players.iter().any(|player| player.id == msg::source()) {
+            return Err(GameError::NotRegistered);
+        }
+
+        self.verify_shuffle_stage()
+    }
+
+    /// Runs the next stage of the shuffle proof verification. The shuffled deck replaces the
+    /// current one only once the last stage has passed.
+    fn verify_shuffle_stage(&mut self) -> Result<GameEvent, GameError> {
+        let joint_pk = self.joint_pk.ok_or(GameError::WrongPhase)?;
+        let pending = self
+            .pending_shuffle
+            .as_mut()
+            .ok_or(GameError::NoShuffleToVerify)?;
+        let player_id = self.players[self.next_shuffler].id;
+
+        let verification = pending.verifier.verify_next_stage(
+            &self.parameters,
+            &joint_pk,
+            &self.deck,
+            &pending.deck,
+            &pending.proof,
+        );
+        match verification {
+            Err(_) => {
+                self.pending_shuffle = None;
+                return Ok(self.slash(self.next_shuffler, GameError::InvalidShuffleProof));
+            }
+            Ok(false) => {
+                return Ok(GameEvent::ShuffleStageVerified {
+                    player: player_id,
+                    verified_stages: pending.verifier.verified_stages() as u8,
+                    stages: ShuffleVerifier::<Curve>::STAGES as u8,
+                });
+            }
+            Ok(true) => {}
+        }
+
+        let pending = self
+            .pending_shuffle
+            .take()
+            .expect("The verified shuffle is pending");
+        self.record(HandRecord::DeckShuffled {
+            player: player_id,
+            input_deck: digest(self.deck.iter().map(serialize)),
+            output_deck: digest(pending.deck.iter().map(serialize)),
+            proof: digest([serialize(&pending.proof)]),
+        });
+        self.deck = pending.deck;
+        self.players[self.next_shuffler].has_shuffled = true;
+        self.next_shuffler += 1;
+
+        let next_shuffler = self.players.get(self.next_shuffler).map(|player| player.id);
+        if next_shuffler.is_none() {
+            self.phase = Phase::Dealing;
+        }
+
+        Ok(GameEvent::DeckShuffled {
+            player: player_id,
+            next_shuffler,
+        })
+    }
 }
-static mut GAME: Option<Game> = None;
+
+impl Game {
+    /// Dispatches an action at this table.
+    fn process(&mut self, action: GameAction) -> Result<GameEvent, GameError> {
+        self.check_phase(&action)?;
+        if msg::value() > 0 && !matches!(action, GameAction::JoinTable { .. }) {
+            return Err(GameError::UnexpectedValue);
+        }
+
+        let waiting_for = self.waiting_for();
+        // The `Timeout` being handled is spent, a new one is armed whatever it changed.
+        let timer_fired = matches!(action, GameAction::Timeout { .. });
+        let result = match action {
+            GameAction::JoinTable {
+                name,
+                pub_key,
+                proof_key_ownership,
+            } => self.add_player(name, pub_key, proof_key_ownership),
+            GameAction::Shuffle {
+                deck,
+                shuffle_proof,
+            } => self.shuffle(deck, shuffle_proof),
+            GameAction::VerifyShuffle => self.verify_shuffle(),
+            GameAction::CloseRegistration => self.close_registration(),
+            GameAction::Deal => self.deal(),
+            GameAction::SubmitRevealToken {
+                card_index,
+                token,
+                proof,
+            } => self.submit_reveal_token(card_index, token, proof),
+            GameAction::PrecommitRevealTokens { tokens } => self.precommit_reveal_tokens(tokens),
+            GameAction::Check => self.bet(betting::Action::Check),
+            GameAction::Call => self.bet(betting::Action::Call),
+            GameAction::Bet { amount } => self.bet(betting::Action::Bet(amount)),
+            GameAction::Raise { to } => self.bet(betting::Action::Raise(to)),
+            GameAction::Fold { tokens } => self.fold(tokens),
+            GameAction::LeaveTable => self.leave_table(),
+            GameAction::Withdraw => self.withdraw(),
+            GameAction::Timeout { timer } => self.timeout(timer),
+        };
+
+        if result.is_ok() {
+            if timer_fired || self.waiting_for() != waiting_for {
+                self.start_timer();
+            }
+            self.report_to_lobby();
+        }
+        result
+    }
+}
+
+impl From<&Game> for GameState {
+    fn from(game: &Game) -> Self {
+        let players = game
+            .players
+            .iter()
+            .enumerate()
+            .map(|(seat, player)| {
+                let bets = game.betting.as_ref().map(|betting| &betting.seats[seat]);
+                PlayerState {
+                    id: player.id,
+                    name: player.name.clone(),
+                    pk: serialize(&player.pk),
+                    has_shuffled: player.has_shuffled,
+                    cards: positions(&player.cards),
+                    stack: bets.map_or(player.stack, |bets| bets.stack),
+                    deposit: player.deposit,
+                    bet: bets.map_or(0, |bets| bets.bet),
+                    folded: bets.is_some_and(|bets| bets.folded),
+                }
+            })
+            .collect();
+
+        Self {
+            game_id: game.id,
+            phase: game.phase,
+            max_players: game.max_players,
+            players,
+            joint_pk: game.joint_pk.as_ref().map(serialize),
+            deck: game.deck.iter().map(serialize).collect(),
+            turn: game.turn(),
+            community_cards: positions(&game.community_cards),
+            revealed_cards: game
+                .revealed_cards
+                .iter()
+                .map(|(&index, &card)| (index as u8, card))
+                .collect(),
+            button: game.button as u8,
+            small_blind: game.small_blind,
+            big_blind: game.big_blind,
+            min_buy_in: game.min_buy_in,
+            max_buy_in: game.max_buy_in,
+            deposit: game.deposit,
+            turn_timeout: game.turn_timeout,
+            deadline: game.deadline,
+            slashed: game.slashed.clone(),
+            balances: game
+                .balances
+                .iter()
+                .map(|(&player, &amount)| (player, amount))
+                .collect(),
+            shuffle_verification: game.pending_shuffle.as_ref().map(|pending| {
+                ShuffleVerificationState {
+                    player: game.players[game.next_shuffler].id,
+                    verified_stages: pending.verifier.verified_stages() as u8,
+                    stages: ShuffleVerifier::<Curve>::STAGES as u8,
+                }
+            }),
+            current_bet: game
+                .betting
+                .as_ref()
+                .map_or(0, |betting| betting.current_bet),
+            pot: game.betting.as_ref().map_or(0, Betting::pot),
+            pots: game
+                .betting
+                .as_ref()
+                .map(|betting| {
+                    betting
+                        .pots()
+                        .into_iter()
+                        .map(|pot| PotState {
+                            amount: pot.amount,
+                            eligible: pot
+                                .eligible
+                                .iter()
+                                .map(|&seat| game.players[seat].id)
+                                .collect(),
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+            hand: game.hand,
+            history: game.history.clone(),
+        }
+    }
+}
+
+/// Shares slashed chips evenly over the stacks of everybody but `offender`, the chips that
+/// cannot be split going one each in seat order. Returns how much was handed out, which is
+/// nothing if nobody else is seated.
+fn compensate(players: &mut [Player], offender: ActorId, amount: u128) -> u128 {
+    let num_of_honest = players
+        .iter()
+        .filter(|player| player.id != offender)
+        .count() as u128;
+    if num_of_honest == 0 {
+        return 0;
+    }
+
+    let share = amount / num_of_honest;
+    let odd_chips = amount % num_of_honest;
+    for (position, player) in players
+        .iter_mut()
+        .filter(|player| player.id != offender)
+        .enumerate()
+    {
+        player.stack += share + u128::from((position as u128) < odd_chips);
+    }
+    amount
+}
+
+/// Pays the pots to the best of `ranks` among their eligible seats and returns the chips
+/// won by every seat. If every seat has folded, nobody can win and the chips go back to the
+/// players who put them in.
+fn settle_pots(betting: &mut Betting, ranks: &[Option<HandRank>]) -> Vec<u128> {
+    let winnings = if betting.seats.iter().all(|seat| seat.folded) {
+        betting.seats.iter().map(|seat| seat.committed).collect()
+    } else {
+        pot::distribute(&betting.pots(), ranks, betting.button)
+    };
+    betting.settle(&winnings);
+    winnings
+}
+
+/// Moves the player's stack and deposit to their balance, from where `Withdraw` sends it.
+/// Returns the amount credited.
+fn pay_out(balances: &mut BTreeMap<ActorId, u128>, player: &mut Player) -> u128 {
+    let amount = mem::take(&mut player.stack) + mem::take(&mut player.deposit);
+    if amount > 0 {
+        *balances.entry(player.id).or_default() += amount;
+    }
+    amount
+}
+
+fn positions(indices: &[usize]) -> Vec<u8> {
+    indices.iter().map(|&index| index as u8).collect()
+}
+
+/// Blake2s over the concatenated serialized `items`, as committed to in the history.
+fn digest(items: impl IntoIterator<Item = Vec<u8>>) -> Digest {
+    let mut hasher = Blake2s::new();
+    for item in items {
+        hasher.update(item);
+    }
+    hasher.finalize().into()
+}
+
+fn serialize<T: CanonicalSerialize>(value: &T) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    value
+        .serialize_uncompressed(&mut bytes)
+        .expect("Unable to serialize");
+    bytes
+}
+
+static mut GAMES: BTreeMap<GameId, Game> = BTreeMap::new();
+/// Id of the next table. Ids of retired tables are not given out again.
+static mut NEXT_GAME_ID: GameId = 0;
 
 #[no_mangle]
 extern "C" fn handle() {
-    let msg: GameAction = msg::load().expect("Unable to load the message");
-    let game = unsafe { GAME.as_mut().expect("The contract is not initialized") };
+    let result = process_handle();
+    // A failed message is not reverted, so the value it carried goes back with the reply.
+    let refund = if result.is_err() { msg::value() } else { 0 };
+    msg::reply(result, refund).expect("Unable to reply");
+}
+
+fn process_handle() -> Result<GameEvent, GameError> {
+    let msg: GameMessage = msg::load().map_err(|_| GameError::MalformedMessage)?;
+    let games = unsafe { &mut GAMES };
 
     match msg {
-        GameAction::AddPlayer {
-            name,
-            pub_key,
-            proof_key_ownership,
-        } => game.add_player(name, pub_key, proof_key_ownership),
-        GameAction::Shuffle {
-            deck,
-            shuffle_proof,
-        } => game.shuffle(deck, shuffle_proof),
+        GameMessage::CreateTable(init_msg) => {
+            if msg::value() > 0 {
+                return Err(GameError::UnexpectedValue);
+            }
+            let game_id = create_table(games, unsafe { &mut NEXT_GAME_ID }, init_msg)?;
+            Ok(GameEvent::TableCreated { game_id })
+        }
+        GameMessage::Action { game_id, action } => games
+            .get_mut(&game_id)
+            .ok_or(GameError::UnknownTable)?
+            .process(action),
+        GameMessage::RetireTable { game_id } => {
+            if msg::value() > 0 {
+                return Err(GameError::UnexpectedValue);
+            }
+            let game = games.get(&game_id).ok_or(GameError::UnknownTable)?;
+            if game.phase != Phase::Finished || !game.balances.is_empty() {
+                return Err(GameError::TableInUse);
+            }
+            games.remove(&game_id);
+            Ok(GameEvent::TableRetired { game_id })
+        }
     }
 }
 
+/// Sets up a table under the next id.
+fn create_table(
+    games: &mut BTreeMap<GameId, Game>,
+    next_game_id: &mut GameId,
+    init_msg: InitGame,
+) -> Result<GameId, GameError> {
+    let game_id = *next_game_id;
+    let mut game = Game::new(game_id, init_msg)?;
+    game.report_to_lobby();
+    games.insert(game_id, game);
+    *next_game_id += 1;
+    Ok(game_id)
+}
+
 #[no_mangle]
 extern "C" fn init() {
-    let init_msg: InitGame = msg::load().expect("Unable to load the init msg");
-
-    debug!("GAS {:?}", exec::gas_available());
-    // let enc_parameters = G1::deserialize_uncompressed_unchecked(&*init_msg.enc_parameters)
-    //     .unwrap();
-    let enc_parameters = G1::deserialize_uncompressed(&*init_msg.enc_parameters)
-        .unwrap();
-    debug!("GAS {:?}", exec::gas_available());
-
-    let enc_parameters = G1::deserialize_uncompressed(&*init_msg.enc_parameters)
-    .unwrap();
-    debug!("GAS {:?}", exec::gas_available());
-
-    let enc_parameters = G1::deserialize_uncompressed(&*init_msg.enc_parameters)
-        .unwrap();
-
-        debug!("GAS {:?}", exec::gas_available());
-   // debug!("HERE {:?}", exec::gas_available());
-    let enc_parameters = el_gamal::Parameters::<starknet_curve::Projective>::deserialize_uncompressed(&*init_msg.enc_parameters)
-        .unwrap();
-
-      debug!("HERE {:?}", exec::gas_available());
-
-
-    // let commit_parameters = CommitKey::<starknet_curve::Projective>::deserialize_uncompressed(&*init_msg.commit_parameters)
-    //     .unwrap();
-
-//   //      debug!("HERE {:?}", exec::gas_available());
-//     let generator = el_gamal::Generator::<G1>::deserialize_uncompressed(&*init_msg.generator)
-//         .unwrap()
-//         .into();
-//     let parameters =
-//         discrete_log_cards::Parameters::new(2, 26, enc_parameters, commit_parameters, generator);
-
-//   //      debug!("HERE");
-//     let game = Game {
-//         parameters,
-//         joint_pk: PublicKey::default(),
-//         players: Vec::new(),
-//         deck: Vec::new(),
-//     };
-//     unsafe { GAME = Some(game) };
+    let result = msg::load::<InitGame>()
+        .map_err(|_| GameError::MalformedMessage)
+        .and_then(|init_msg| {
+            create_table(
+                unsafe { &mut GAMES },
+                unsafe { &mut NEXT_GAME_ID },
+                init_msg,
+            )
+        })
+        .map(|_| ());
+    msg::reply(result, 0).expect("Unable to reply");
+}
+
+#[no_mangle]
+extern "C" fn state() {
+    let tables = unsafe { GAMES.values().map(GameState::from).collect() };
+    msg::reply(ProgramState { tables }, 0).expect("Unable to share the state");
+}
+
+#[no_mangle]
+extern "C" fn metahash() {
+    let metahash: [u8; 32] = include!("../.metahash");
+    msg::reply(metahash, 0).expect("Unable to share the metahash");
 }
@@ -0,0 +1,85 @@
+//! Splitting the chips of a hand into a main pot and side pots, and paying them out.
+
+use crate::betting::Seat;
+use crate::hand::HandRank;
+use gstd::prelude::*;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pot {
+    pub amount: u128,
+    /// Seats that can win the pot: the ones still in the hand that matched its level.
+    pub eligible: Vec<usize>,
+}
+
+/// Cuts the chips committed during a hand at every level a remaining player went all-in
+/// for. The first pot is the main pot, each following one is contested by fewer players.
+/// Folded chips go to the pots their owner paid into.
+pub fn side_pots(seats: &[Seat]) -> Vec<Pot> {
+    let mut levels = seats
+        .iter()
+        .filter(|seat| !seat.folded)
+        .map(|seat| seat.committed)
+        .collect::<Vec<_>>();
+    levels.sort_unstable();
+    levels.dedup();
+
+    let mut pots: Vec<Pot> = Vec::new();
+    let mut previous_level = 0;
+    for level in levels {
+        let amount = seats
+            .iter()
+            .map(|seat| seat.committed.min(level) - seat.committed.min(previous_level))
+            .sum();
+        let eligible = seats
+            .iter()
+            .enumerate()
+            .filter(|(_, seat)| !seat.folded && seat.committed >= level)
+            .map(|(index, _)| index)
+            .collect();
+        if amount > 0 {
+            pots.push(Pot { amount, eligible });
+        }
+        previous_level = level;
+    }
+
+    // Folded players may have put in more than anybody who is still in the hand.
+    let dead_chips: u128 = seats
+        .iter()
+        .map(|seat| seat.committed.saturating_sub(previous_level))
+        .sum();
+    if let Some(last) = pots.last_mut() {
+        last.amount += dead_chips;
+    }
+
+    pots
+}
+
+/// Awards every pot to the best hand among its eligible seats and returns the chips won by
+/// each seat. `ranks` holds the shown hand of every seat, `None` when a seat has no claim.
+/// If no eligible seat has a claim, the pot is shared by all of its eligible seats.
+///
+/// Tied winners split a pot evenly. The chips that cannot be split go one by one to the
+/// winners in seat order starting left of the button.
+pub fn distribute(pots: &[Pot], ranks: &[Option<HandRank>], button: usize) -> Vec<u128> {
+    let num_of_seats = ranks.len();
+    let mut winnings = vec![0; num_of_seats];
+
+    for pot in pots {
+        let best = pot.eligible.iter().filter_map(|&seat| ranks[seat]).max();
+        let mut winners = pot
+            .eligible
+            .iter()
+            .copied()
+            .filter(|&seat| best.is_none() || ranks[seat] == best)
+            .collect::<Vec<_>>();
+        winners.sort_unstable_by_key(|&seat| (seat + num_of_seats - button - 1) % num_of_seats);
+
+        let share = pot.amount / winners.len() as u128;
+        let odd_chips = (pot.amount % winners.len() as u128) as usize;
+        for (position, &seat) in winners.iter().enumerate() {
+            winnings[seat] += share + u128::from(position < odd_chips);
+        }
+    }
+
+    winnings
+}
@@ -0,0 +1,47 @@
+#![no_std]
+
+//! State functions of the `sc_poker` program, so a client can read one table instead of
+//! decoding every table with its deck and history.
+
+use gmeta::metawasm;
+use gstd::prelude::*;
+use sc_poker_io::{GameId, GameState, HistoryEntry, Phase, ProgramState};
+
+#[metawasm]
+pub mod metafns {
+    pub type State = ProgramState;
+
+    /// The table `game_id`, if it exists.
+    pub fn table(state: State, game_id: GameId) -> Option<GameState> {
+        state
+            .tables
+            .into_iter()
+            .find(|table| table.game_id == game_id)
+    }
+
+    /// The history entries of hand `hand` at the table `game_id`, in the order they were
+    /// accepted. Empty if the table or the hand does not exist.
+    pub fn hand_history(state: State, game_id: GameId, hand: u32) -> Vec<HistoryEntry> {
+        state
+            .tables
+            .into_iter()
+            .find(|table| table.game_id == game_id)
+            .map(|table| {
+                table
+                    .history
+                    .into_iter()
+                    .filter(|entry| entry.hand == hand)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Id, phase and number of seats taken of every table.
+    pub fn tables(state: State) -> Vec<(GameId, Phase, u8)> {
+        state
+            .tables
+            .into_iter()
+            .map(|table| (table.game_id, table.phase, table.players.len() as u8))
+            .collect()
+    }
+}
@@ -0,0 +1,158 @@
+use sc_poker::betting::{Action, Betting};
+use sc_poker_io::GameError;
+
+#[test]
+fn blinds_and_preflop_order() {
+    // Button on seat 0, small blind on seat 1, big blind on seat 2.
+    let mut betting = Betting::new(&[100, 100, 100, 100], 0, 1, 2);
+    assert_eq!(betting.seats[1].bet, 1);
+    assert_eq!(betting.seats[2].bet, 2);
+    assert_eq!(betting.current_bet, 2);
+    assert_eq!(betting.to_act, Some(3));
+
+    assert_eq!(betting.act(0, Action::Call), Err(GameError::NotYourTurn));
+    assert_eq!(betting.act(3, Action::Check), Err(GameError::CannotCheck));
+    betting.act(3, Action::Call).unwrap();
+    betting.act(0, Action::Call).unwrap();
+    betting.act(1, Action::Call).unwrap();
+
+    // The big blind has the option to raise once everybody limped in.
+    assert_eq!(betting.to_act, Some(2));
+    betting.act(2, Action::Check).unwrap();
+    assert_eq!(betting.to_act, None);
+    assert_eq!(betting.pot(), 8);
+
+    // After the flop the first seat after the button opens the action.
+    betting.next_street();
+    assert_eq!(betting.to_act, Some(1));
+    assert_eq!(betting.act(1, Action::Call), Err(GameError::NothingToCall));
+    assert_eq!(
+        betting.act(1, Action::Raise(4)),
+        Err(GameError::NoBetToRaise)
+    );
+}
+
+#[test]
+fn heads_up_button_posts_small_blind() {
+    let mut betting = Betting::new(&[100, 100], 1, 1, 2);
+    assert_eq!(betting.seats[1].bet, 1);
+    assert_eq!(betting.seats[0].bet, 2);
+    assert_eq!(betting.to_act, Some(1));
+
+    betting.act(1, Action::Call).unwrap();
+    betting.act(0, Action::Check).unwrap();
+    assert_eq!(betting.to_act, None);
+
+    betting.next_street();
+    assert_eq!(betting.to_act, Some(0));
+}
+
+#[test]
+fn minimum_bets_and_raises() {
+    let mut betting = Betting::new(&[100, 100, 100], 0, 5, 10);
+    // Seat 0 is the button and first to act with three players.
+    assert_eq!(
+        betting.act(0, Action::Raise(15)),
+        Err(GameError::BetTooSmall)
+    );
+    assert_eq!(
+        betting.act(0, Action::Bet(20)),
+        Err(GameError::BetAlreadyMade)
+    );
+    assert_eq!(
+        betting.act(0, Action::Raise(101)),
+        Err(GameError::NotEnoughChips)
+    );
+    betting.act(0, Action::Raise(30)).unwrap();
+    assert_eq!(betting.min_raise, 20);
+
+    assert_eq!(
+        betting.act(1, Action::Raise(45)),
+        Err(GameError::BetTooSmall)
+    );
+    betting.act(1, Action::Raise(50)).unwrap();
+    betting.act(2, Action::Fold).unwrap();
+    betting.act(0, Action::Call).unwrap();
+    assert_eq!(betting.to_act, None);
+
+    betting.next_street();
+    assert_eq!(betting.to_act, Some(1));
+    assert_eq!(betting.act(1, Action::Bet(5)), Err(GameError::BetTooSmall));
+    betting.act(1, Action::Bet(10)).unwrap();
+    assert_eq!(betting.to_act, Some(0));
+}
+
+#[test]
+fn incomplete_all_in_raise_does_not_reopen_betting() {
+    let mut betting = Betting::new(&[100, 100, 25], 0, 5, 10);
+    betting.act(0, Action::Raise(20)).unwrap();
+    betting.act(1, Action::Call).unwrap();
+    // Five chips more than the current bet is short of a full raise of ten.
+    betting.act(2, Action::Raise(25)).unwrap();
+    assert!(betting.seats[2].is_all_in());
+
+    assert_eq!(betting.to_act, Some(0));
+    assert_eq!(
+        betting.act(0, Action::Raise(60)),
+        Err(GameError::RaiseNotAllowed)
+    );
+    betting.act(0, Action::Call).unwrap();
+    betting.act(1, Action::Call).unwrap();
+    assert_eq!(betting.to_act, None);
+    assert_eq!(betting.pot(), 75);
+}
+
+#[test]
+fn all_in_players_are_skipped() {
+    let mut betting = Betting::new(&[100, 40, 100], 0, 5, 10);
+    betting.act(0, Action::Call).unwrap();
+    betting.act(1, Action::Raise(40)).unwrap();
+    betting.act(2, Action::Call).unwrap();
+    betting.act(0, Action::Call).unwrap();
+    assert_eq!(betting.to_act, None);
+
+    betting.next_street();
+    assert_eq!(betting.to_act, Some(2));
+    betting.act(2, Action::Check).unwrap();
+    betting.act(0, Action::Bet(60)).unwrap();
+    assert!(betting.seats[0].is_all_in());
+    // There is nobody left to raise against.
+    assert_eq!(
+        betting.act(2, Action::Raise(120)),
+        Err(GameError::RaiseNotAllowed)
+    );
+    betting.act(2, Action::Call).unwrap();
+
+    // Only one player has chips behind, so later streets have no betting at all.
+    betting.next_street();
+    assert_eq!(betting.to_act, None);
+}
+
+#[test]
+fn short_call_puts_the_stack_in() {
+    let mut betting = Betting::new(&[100, 30], 1, 5, 10);
+    betting.act(1, Action::Call).unwrap();
+    betting.act(0, Action::Check).unwrap();
+
+    betting.next_street();
+    betting.act(0, Action::Bet(50)).unwrap();
+    betting.act(1, Action::Call).unwrap();
+    assert!(betting.seats[1].is_all_in());
+    assert_eq!(betting.seats[1].committed, 30);
+    assert_eq!(betting.to_act, None);
+}
+
+#[test]
+fn last_player_standing_collects_the_pot() {
+    let mut betting = Betting::new(&[100, 100, 100], 0, 5, 10);
+    betting.act(0, Action::Raise(30)).unwrap();
+    betting.act(1, Action::Fold).unwrap();
+    assert_eq!(betting.last_player_standing(), None);
+    betting.act(2, Action::Fold).unwrap();
+
+    assert_eq!(betting.last_player_standing(), Some(0));
+    assert_eq!(betting.to_act, None);
+    assert_eq!(betting.award_pot(0), 45);
+    assert_eq!(betting.seats[0].stack, 115);
+    assert_eq!(betting.pot(), 0);
+}
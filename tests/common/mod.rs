@@ -0,0 +1,393 @@
+#![allow(dead_code)]
+
+//! Drives a table in `gtest` the way the players' clients would: keys, shuffles, reveal
+//! tokens and bets are computed off-chain with the full card protocol and sent serialized.
+
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use barnett_smart_card_protocol::discrete_log_cards;
+use barnett_smart_card_protocol::BarnettSmartProtocol;
+use gstd::prelude::Decode;
+use gstd::ActorId;
+use gtest::{Program, RunResult, System};
+use proof_essentials::utils::permutation::Permutation;
+use proof_essentials::utils::rand::sample_vector;
+use rand::rngs::ThreadRng;
+use rand::thread_rng;
+use sc_poker_io::encoding::Curve;
+use sc_poker_io::*;
+
+pub type CardProtocol<'a> = discrete_log_cards::DLCards<'a, Curve>;
+pub type CardParameters = discrete_log_cards::Parameters<Curve>;
+pub type PublicKey = discrete_log_cards::PublicKey<Curve>;
+pub type SecretKey = discrete_log_cards::PlayerSecretKey<Curve>;
+pub type MaskedCard = discrete_log_cards::MaskedCard<Curve>;
+
+pub const ADMIN: u64 = 100;
+pub const DEPOSIT: u128 = 50;
+pub const BUY_IN: u128 = 100;
+
+pub struct Player {
+    pub id: u64,
+    pk: PublicKey,
+    sk: SecretKey,
+}
+
+impl Player {
+    pub fn actor_id(&self) -> ActorId {
+        self.id.into()
+    }
+}
+
+pub struct Table<'a> {
+    pub system: &'a System,
+    pub program: Program<'a>,
+    pub game_id: GameId,
+    pub parameters: CardParameters,
+    rng: ThreadRng,
+}
+
+impl<'a> Table<'a> {
+    /// Sets up table 0 with blinds of 1 and 2 and room for `max_players`.
+    pub fn new(system: &'a System, max_players: u8, turn_timeout: u32) -> Self {
+        let mut rng = thread_rng();
+        let parameters = CardProtocol::setup(&mut rng, 2, 26).unwrap();
+        let program = Program::current(system);
+        let result = program.send(ADMIN, init_game(&parameters, max_players, turn_timeout));
+        assert!(!result.main_failed());
+
+        Self {
+            system,
+            program,
+            game_id: 0,
+            parameters,
+            rng,
+        }
+    }
+
+    /// Opens another table of the same program with `CreateTable`, set up like `new` does.
+    pub fn create(&self, max_players: u8, turn_timeout: u32) -> Table<'a> {
+        let mut rng = thread_rng();
+        let parameters = CardProtocol::setup(&mut rng, 2, 26).unwrap();
+        let program = self.system.get_program(self.program.id());
+        let init_msg = init_game(&parameters, max_players, turn_timeout);
+        let result = program.send(ADMIN, GameMessage::CreateTable(init_msg));
+        assert!(!result.main_failed());
+        let game_id = match reply(&result) {
+            Ok(GameEvent::TableCreated { game_id }) => game_id,
+            reply => panic!("Unexpected reply {reply:?}"),
+        };
+
+        Table {
+            system: self.system,
+            program,
+            game_id,
+            parameters,
+            rng,
+        }
+    }
+
+    /// Asks the program to retire this table and returns its reply.
+    pub fn retire(&self) -> Result<GameEvent, GameError> {
+        let result = self.program.send(
+            ADMIN,
+            GameMessage::RetireTable {
+                game_id: self.game_id,
+            },
+        );
+        assert!(!result.main_failed());
+        reply(&result)
+    }
+
+    pub fn state(&self) -> GameState {
+        let state: ProgramState = self.program.read_state().unwrap();
+        state.table(self.game_id).cloned().unwrap()
+    }
+
+    pub fn send(&self, player: &Player, action: GameAction) -> RunResult {
+        let result = self.program.send(
+            player.id,
+            GameMessage::Action {
+                game_id: self.game_id,
+                action,
+            },
+        );
+        assert!(!result.main_failed());
+        result
+    }
+
+    /// Whether the table replied `reply` to `player`.
+    pub fn replied(
+        &self,
+        result: &RunResult,
+        player: &Player,
+        reply: Result<GameEvent, GameError>,
+    ) -> bool {
+        result.contains(&gtest::Log::builder().dest(player.id).payload(reply))
+    }
+
+    /// Seats a new player with a buy-in of `BUY_IN`.
+    pub fn join(&mut self, id: u64) -> Player {
+        self.join_with_proof_for(id, id)
+    }
+
+    /// Joins as `id` with a key ownership proof bound to the address `proof_for`, which
+    /// only verifies if it is the player's own.
+    pub fn join_with_proof_for(&mut self, id: u64, proof_for: u64) -> Player {
+        let (pk, sk) = CardProtocol::player_keygen(&mut self.rng, &self.parameters).unwrap();
+        let mut public_info = [0u8; 32];
+        public_info.copy_from_slice(ActorId::from(proof_for).as_ref());
+        let proof = CardProtocol::prove_key_ownership(
+            &mut self.rng,
+            &self.parameters,
+            &pk,
+            &sk,
+            &public_info,
+        )
+        .unwrap();
+
+        self.system.mint_to(id, DEPOSIT + BUY_IN);
+        let action = GameAction::JoinTable {
+            name: format!("Player {id}"),
+            pub_key: serialize(&pk),
+            proof_key_ownership: serialize(&proof),
+        };
+        let result = self.program.send_with_value(
+            id,
+            GameMessage::Action {
+                game_id: self.game_id,
+                action,
+            },
+            DEPOSIT + BUY_IN,
+        );
+        assert!(!result.main_failed());
+
+        Player { id, pk, sk }
+    }
+
+    /// Shuffles the current deck as `player` and submits it. A dishonest shuffle sends a
+    /// deck its proof was not made for.
+    pub fn submit_shuffle(&mut self, player: &Player, honest: bool) -> RunResult {
+        let state = self.state();
+        let joint_pk = deserialize::<PublicKey>(state.joint_pk.as_ref().unwrap());
+        let deck = state
+            .deck
+            .iter()
+            .map(|card| deserialize::<MaskedCard>(card))
+            .collect::<Vec<_>>();
+
+        let permutation = Permutation::new(&mut self.rng, NUM_OF_CARDS);
+        let masking_factors = sample_vector(&mut self.rng, NUM_OF_CARDS);
+        let (shuffled_deck, proof) = CardProtocol::shuffle_and_remask(
+            &mut self.rng,
+            &self.parameters,
+            &joint_pk,
+            &deck,
+            &masking_factors,
+            &permutation,
+        )
+        .unwrap();
+        let mut sent_deck = shuffled_deck;
+        if !honest {
+            sent_deck[0] = deck[0];
+        }
+
+        self.send(
+            player,
+            GameAction::Shuffle {
+                deck: sent_deck.iter().map(serialize).collect(),
+                shuffle_proof: serialize(&proof),
+            },
+        )
+    }
+
+    /// Shuffles as `player` and runs the verification to the end.
+    pub fn shuffle(&mut self, player: &Player, honest: bool) {
+        self.submit_shuffle(player, honest);
+        while self.state().shuffle_verification.is_some() {
+            self.send(player, GameAction::VerifyShuffle);
+        }
+    }
+
+    /// Shuffles honestly as every player in seat order and deals.
+    pub fn shuffle_and_deal(&mut self, players: &[&Player]) {
+        for player in self.seated(players) {
+            self.shuffle(player, true);
+        }
+        assert_eq!(self.state().phase, Phase::Dealing);
+        self.send(players[0], GameAction::Deal);
+        assert_eq!(self.state().phase, Phase::PreFlop);
+    }
+
+    /// `players` ordered by their seats.
+    pub fn seated<'p>(&self, players: &[&'p Player]) -> Vec<&'p Player> {
+        self.state()
+            .players
+            .iter()
+            .map(|seat| {
+                *players
+                    .iter()
+                    .find(|player| player.actor_id() == seat.id)
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    /// `player`'s reveal token for the card at `card_index`.
+    pub fn reveal_token_share(&mut self, player: &Player, card_index: u8) -> RevealTokenShare {
+        self.forged_token_share(player, card_index, card_index)
+    }
+
+    /// A share for the card at `card_index` holding `player`'s token for the card at
+    /// `computed_for`, whose proof only verifies if it is the same card.
+    pub fn forged_token_share(
+        &mut self,
+        player: &Player,
+        card_index: u8,
+        computed_for: u8,
+    ) -> RevealTokenShare {
+        let state = self.state();
+        let masked_card = deserialize::<MaskedCard>(&state.deck[computed_for as usize]);
+        let (token, proof) = CardProtocol::compute_reveal_token(
+            &mut self.rng,
+            &self.parameters,
+            &player.sk,
+            &player.pk,
+            &masked_card,
+        )
+        .unwrap();
+
+        RevealTokenShare {
+            card_index,
+            token: serialize(&token),
+            proof: serialize(&proof),
+        }
+    }
+
+    pub fn submit_token(&mut self, player: &Player, card_index: u8) -> RunResult {
+        let share = self.reveal_token_share(player, card_index);
+        self.submit_share(player, share)
+    }
+
+    pub fn submit_share(&self, player: &Player, share: RevealTokenShare) -> RunResult {
+        self.send(
+            player,
+            GameAction::SubmitRevealToken {
+                card_index: share.card_index,
+                token: share.token,
+                proof: share.proof,
+            },
+        )
+    }
+
+    /// Submits `player`'s tokens for the hole cards of everybody else.
+    pub fn submit_hole_tokens(&mut self, player: &Player) {
+        let others_cards = self
+            .state()
+            .players
+            .iter()
+            .filter(|seat| seat.id != player.actor_id())
+            .flat_map(|seat| seat.cards.clone())
+            .collect::<Vec<_>>();
+        for card_index in others_cards {
+            self.submit_token(player, card_index);
+        }
+    }
+
+    /// Submits `player`'s tokens for their own hole cards.
+    pub fn show(&mut self, player: &Player) {
+        let state = self.state();
+        let own = state
+            .players
+            .iter()
+            .find(|seat| seat.id == player.actor_id())
+            .unwrap();
+        for &card_index in &own.cards {
+            self.submit_token(player, card_index);
+        }
+    }
+
+    /// Submits the tokens of `players` for the street waiting to be opened.
+    pub fn open_street(&mut self, players: &[&Player]) {
+        let state = self.state();
+        let street = match state.phase {
+            Phase::Flop => 0..3,
+            Phase::Turn => 3..4,
+            Phase::River => 4..5,
+            phase => panic!("No street to open in {phase:?}"),
+        };
+        for &card_index in &state.community_cards[street] {
+            for player in players {
+                self.submit_token(player, card_index);
+            }
+        }
+    }
+
+    /// Checks or calls for whoever is to act until the betting round is over.
+    pub fn check_down(&mut self, players: &[&Player]) {
+        while let Some(turn) = self.state().turn {
+            let state = self.state();
+            let player = players
+                .iter()
+                .find(|player| player.actor_id() == turn)
+                .unwrap();
+            let seat = state.players.iter().find(|seat| seat.id == turn).unwrap();
+            let action = if seat.bet < state.current_bet {
+                GameAction::Call
+            } else {
+                GameAction::Check
+            };
+            self.send(player, action);
+        }
+    }
+
+    /// The player the table waits for to bet.
+    pub fn to_act<'p>(&self, players: &[&'p Player]) -> &'p Player {
+        let turn = self.state().turn.unwrap();
+        players
+            .iter()
+            .find(|player| player.actor_id() == turn)
+            .unwrap()
+    }
+
+    /// Lets the deadline of the table pass.
+    pub fn time_out(&self) {
+        let deadline = self.state().deadline.unwrap();
+        self.system
+            .spend_blocks(deadline - self.system.block_height() + 1);
+    }
+}
+
+fn init_game(parameters: &CardParameters, max_players: u8, turn_timeout: u32) -> InitGame {
+    InitGame {
+        enc_parameters: serialize(&parameters.enc_parameters),
+        commit_parameters: serialize(&parameters.commit_parameters),
+        generator: serialize(&parameters.generator),
+        max_players,
+        small_blind: 1,
+        big_blind: 2,
+        min_buy_in: BUY_IN,
+        max_buy_in: 10 * BUY_IN,
+        deposit: DEPOSIT,
+        turn_timeout,
+        lobby: None,
+    }
+}
+
+/// The table's reply to `ADMIN`, the only message it sends them.
+fn reply(result: &RunResult) -> Result<GameEvent, GameError> {
+    result
+        .log()
+        .iter()
+        .find_map(|log| Result::<GameEvent, GameError>::decode(&mut log.payload()).ok())
+        .expect("The table replies")
+}
+
+fn serialize<T: CanonicalSerialize>(value: &T) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    value.serialize_uncompressed(&mut bytes).unwrap();
+    bytes
+}
+
+fn deserialize<T: CanonicalDeserialize>(bytes: &[u8]) -> T {
+    T::deserialize_uncompressed(bytes).unwrap()
+}
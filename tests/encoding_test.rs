@@ -0,0 +1,31 @@
+use ark_ff::Zero;
+use sc_poker_io::encoding::{decode_card, encode_card, encode_deck, Card};
+use sc_poker_io::{ClassicPlayingCard, Suite, Value, NUM_OF_CARDS};
+
+#[test]
+fn encoding_is_deterministic() {
+    let ace_of_spades = ClassicPlayingCard::new(Value::Ace, Suite::Spade);
+
+    assert_eq!(encode_card(ace_of_spades), encode_card(ace_of_spades));
+    assert_ne!(
+        encode_card(ace_of_spades),
+        encode_card(ClassicPlayingCard::new(Value::Ace, Suite::Heart))
+    );
+}
+
+#[test]
+fn deck_round_trips() {
+    let deck = encode_deck();
+    assert_eq!(deck.len(), NUM_OF_CARDS);
+
+    for (index, plaintext) in deck.iter().enumerate() {
+        assert!(plaintext.0.is_on_curve());
+        assert!(!deck[index + 1..].contains(plaintext));
+        assert_eq!(
+            decode_card(&deck, plaintext),
+            Some(ClassicPlayingCard::from_index(index))
+        );
+    }
+
+    assert_eq!(decode_card(&deck, &Card::zero()), None);
+}
@@ -0,0 +1,124 @@
+use sc_poker::hand::{evaluate, rank_five, winners};
+use sc_poker_io::{ClassicPlayingCard, HandCategory, Suite, Value};
+
+fn hand(cards: &str) -> Vec<ClassicPlayingCard> {
+    cards
+        .split_whitespace()
+        .map(|code| {
+            let mut chars = code.chars();
+            let value = match chars.next().unwrap() {
+                '2' => Value::Two,
+                '3' => Value::Three,
+                '4' => Value::Four,
+                '5' => Value::Five,
+                '6' => Value::Six,
+                '7' => Value::Seven,
+                '8' => Value::Eight,
+                '9' => Value::Nine,
+                'T' => Value::Ten,
+                'J' => Value::Jack,
+                'Q' => Value::Queen,
+                'K' => Value::King,
+                'A' => Value::Ace,
+                other => panic!("unknown value {other}"),
+            };
+            let suite = match chars.next().unwrap() {
+                'c' => Suite::Club,
+                'd' => Suite::Diamond,
+                'h' => Suite::Heart,
+                's' => Suite::Spade,
+                other => panic!("unknown suite {other}"),
+            };
+            ClassicPlayingCard::new(value, suite)
+        })
+        .collect()
+}
+
+#[test]
+fn categories() {
+    let cases = [
+        ("2c 7d 9h Js Kc", HandCategory::HighCard),
+        ("2c 2d 9h Js Kc", HandCategory::OnePair),
+        ("2c 2d 9h 9s Kc", HandCategory::TwoPair),
+        ("2c 2d 2h Js Kc", HandCategory::ThreeOfAKind),
+        ("9c Td Jh Qs Kc", HandCategory::Straight),
+        ("2c 7c 9c Jc Kc", HandCategory::Flush),
+        ("2c 2d 2h Ks Kc", HandCategory::FullHouse),
+        ("2c 2d 2h 2s Kc", HandCategory::FourOfAKind),
+        ("9h Th Jh Qh Kh", HandCategory::StraightFlush),
+        ("Ts Js Qs Ks As", HandCategory::RoyalFlush),
+    ];
+
+    let mut previous = None;
+    for (cards, category) in cases {
+        let rank = evaluate(&hand(cards));
+        assert_eq!(rank.category, category, "{cards}");
+        assert!(previous < Some(rank), "{cards}");
+        previous = Some(rank);
+    }
+}
+
+#[test]
+fn wheel_is_the_lowest_straight() {
+    let wheel = evaluate(&hand("Ac 2d 3h 4s 5c"));
+    let six_high = evaluate(&hand("2d 3h 4s 5c 6c"));
+    assert_eq!(wheel.category, HandCategory::Straight);
+    assert_eq!(wheel.values[0], Value::Five);
+    assert!(wheel < six_high);
+
+    let steel_wheel = evaluate(&hand("Ad 2d 3d 4d 5d"));
+    assert_eq!(steel_wheel.category, HandCategory::StraightFlush);
+
+    // Straights do not wrap around the ace.
+    assert_eq!(
+        evaluate(&hand("Qc Kd Ah 2s 3c")).category,
+        HandCategory::HighCard
+    );
+}
+
+#[test]
+fn kickers_break_ties() {
+    assert!(evaluate(&hand("Ac Ad 9h 7s 3c")) < evaluate(&hand("Ah As Th 2s 3d")));
+    assert!(evaluate(&hand("Kc Kd 2h 2s 3c")) < evaluate(&hand("Kh Ks 3h 3s 2d")));
+    assert!(evaluate(&hand("Kc Kd 3h 3s 2c")) < evaluate(&hand("Kh Ks 3c 3d 4d")));
+    assert!(evaluate(&hand("2c 2d 2h Ks Kc")) < evaluate(&hand("3c 3d 3h 4s 4c")));
+    assert!(evaluate(&hand("Ac Qc 9c 7c 3c")) < evaluate(&hand("Ah Qh 9h 7h 4h")));
+}
+
+#[test]
+fn best_five_of_seven() {
+    let rank = evaluate(&hand("Ah Kh 2c 7h 9h 3h Kd"));
+    assert_eq!(rank.category, HandCategory::Flush);
+    assert_eq!(
+        rank.values,
+        [
+            Value::Ace,
+            Value::King,
+            Value::Nine,
+            Value::Seven,
+            Value::Three
+        ]
+    );
+
+    // The board plays for both players.
+    let board = "Tc Jd Qh Ks Ac";
+    let first = evaluate(&hand(&format!("{board} 2c 3d")));
+    let second = evaluate(&hand(&format!("{board} 4h 5s")));
+    assert_eq!(first.category, HandCategory::Straight);
+    assert_eq!(winners(&[first, second]), vec![0, 1]);
+
+    let five = hand("Tc Jd Qh Ks Ac");
+    assert_eq!(evaluate(&five), rank_five(&five.try_into().unwrap()));
+}
+
+#[test]
+fn winners_picks_the_best_hand() {
+    let board = "2c 7d 9h Js Kc";
+    let hands = [
+        evaluate(&hand(&format!("{board} 3d 4d"))),
+        evaluate(&hand(&format!("{board} Kd 2d"))),
+        evaluate(&hand(&format!("{board} Ks 3s"))),
+    ];
+    assert_eq!(winners(&hands), vec![1]);
+    assert!(winners(&[]).is_empty());
+}
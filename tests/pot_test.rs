@@ -0,0 +1,105 @@
+use sc_poker::betting::{Action, Betting};
+use sc_poker::hand::evaluate;
+use sc_poker::pot::{distribute, Pot};
+use sc_poker_io::{ClassicPlayingCard, Suite, Value};
+
+fn pot(amount: u128, eligible: &[usize]) -> Pot {
+    Pot {
+        amount,
+        eligible: eligible.to_vec(),
+    }
+}
+
+#[test]
+fn short_all_in_creates_side_pots() {
+    let mut betting = Betting::new(&[100, 30, 60, 100], 0, 5, 10);
+    betting.act(3, Action::Raise(100)).unwrap();
+    betting.act(0, Action::Fold).unwrap();
+    betting.act(1, Action::Call).unwrap();
+    betting.act(2, Action::Call).unwrap();
+    assert_eq!(betting.to_act, None);
+
+    assert_eq!(
+        betting.pots(),
+        vec![pot(90, &[1, 2, 3]), pot(60, &[2, 3]), pot(40, &[3])]
+    );
+    assert_eq!(betting.pot(), 190);
+}
+
+#[test]
+fn folded_chips_stay_in_the_pots() {
+    let mut betting = Betting::new(&[100, 100, 20], 0, 5, 10);
+    betting.act(0, Action::Raise(40)).unwrap();
+    betting.act(1, Action::Call).unwrap();
+    betting.act(2, Action::Call).unwrap();
+    assert_eq!(betting.to_act, None);
+
+    betting.next_street();
+    betting.act(1, Action::Bet(50)).unwrap();
+    betting.act(0, Action::Fold).unwrap();
+
+    // Seat 0 folded after paying 40, and the 50 chip bet nobody called goes back to
+    // seat 1 as part of the only pot above the all-in.
+    assert_eq!(betting.pots(), vec![pot(60, &[1, 2]), pot(90, &[1])]);
+}
+
+#[test]
+fn each_pot_goes_to_its_best_eligible_hand() {
+    let best = evaluate(&[
+        ClassicPlayingCard::new(Value::Ace, Suite::Spade),
+        ClassicPlayingCard::new(Value::Ace, Suite::Heart),
+        ClassicPlayingCard::new(Value::Ace, Suite::Club),
+        ClassicPlayingCard::new(Value::Two, Suite::Diamond),
+        ClassicPlayingCard::new(Value::Seven, Suite::Diamond),
+    ]);
+    let worse = evaluate(&[
+        ClassicPlayingCard::new(Value::King, Suite::Spade),
+        ClassicPlayingCard::new(Value::King, Suite::Heart),
+        ClassicPlayingCard::new(Value::Four, Suite::Club),
+        ClassicPlayingCard::new(Value::Two, Suite::Club),
+        ClassicPlayingCard::new(Value::Seven, Suite::Club),
+    ]);
+
+    // The short stack wins the main pot, the side pot goes to the best of the others.
+    let pots = [pot(90, &[0, 1, 2]), pot(60, &[1, 2])];
+    let ranks = [Some(best), Some(worse), None];
+    assert_eq!(distribute(&pots, &ranks, 0), vec![90, 60, 0]);
+}
+
+#[test]
+fn odd_chips_go_left_of_the_button() {
+    let hand = evaluate(&[
+        ClassicPlayingCard::new(Value::Ace, Suite::Spade),
+        ClassicPlayingCard::new(Value::King, Suite::Heart),
+        ClassicPlayingCard::new(Value::Nine, Suite::Club),
+        ClassicPlayingCard::new(Value::Five, Suite::Diamond),
+        ClassicPlayingCard::new(Value::Three, Suite::Diamond),
+    ]);
+    let ranks = [Some(hand), Some(hand), None, Some(hand)];
+    let pots = [pot(101, &[0, 1, 3])];
+
+    assert_eq!(distribute(&pots, &ranks, 0), vec![33, 34, 0, 34]);
+    assert_eq!(distribute(&pots, &ranks, 1), vec![34, 33, 0, 34]);
+    assert_eq!(distribute(&pots, &ranks, 3), vec![34, 34, 0, 33]);
+}
+
+#[test]
+fn forfeited_hand_is_shared_by_the_others() {
+    let mut betting = Betting::new(&[100, 100, 100], 0, 5, 10);
+    betting.act(0, Action::Call).unwrap();
+    betting.act(1, Action::Call).unwrap();
+    betting.act(2, Action::Check).unwrap();
+
+    betting.forfeit(2);
+    let winnings = distribute(&betting.pots(), &[None, None, None], betting.button);
+    assert_eq!(winnings, vec![15, 15, 0]);
+
+    betting.settle(&winnings);
+    let stacks = betting
+        .seats
+        .iter()
+        .map(|seat| seat.stack)
+        .collect::<Vec<_>>();
+    assert_eq!(stacks, vec![105, 105, 90]);
+    assert_eq!(betting.pot(), 0);
+}
@@ -0,0 +1,130 @@
+mod common;
+
+use common::*;
+use gtest::System;
+use sc_poker_io::*;
+
+/// Seats three players, deals and exchanges the hole card tokens. Returns the player first
+/// to act and the two others.
+fn deal<'p>(table: &mut Table, players: [&'p Player; 3]) -> (&'p Player, [&'p Player; 2]) {
+    table.shuffle_and_deal(&players);
+    for player in players {
+        table.submit_hole_tokens(player);
+    }
+    let folder = table.to_act(&players);
+    let others = players
+        .into_iter()
+        .filter(|player| player.id != folder.id)
+        .collect::<Vec<_>>();
+    (folder, [others[0], others[1]])
+}
+
+fn folded(table: &Table, player: &Player) -> bool {
+    table
+        .state()
+        .players
+        .iter()
+        .find(|seat| seat.id == player.actor_id())
+        .unwrap()
+        .folded
+}
+
+#[test]
+fn streets_open_with_the_tokens_sent_with_a_fold() {
+    let system = System::new();
+    system.init_logger();
+    let mut table = Table::new(&system, 3, 100);
+    let alice = table.join(1);
+    let bob = table.join(2);
+    let carol = table.join(3);
+    let (folder, others) = deal(&mut table, [&alice, &bob, &carol]);
+
+    let community_cards = table.state().community_cards;
+    let tokens = community_cards
+        .iter()
+        .map(|&card_index| table.reveal_token_share(folder, card_index))
+        .collect();
+    table.send(folder, GameAction::Fold { tokens });
+    assert!(folded(&table, folder));
+    let state = table.state();
+    for &card_index in &community_cards {
+        assert!(state.hand_history(0).any(|record| matches!(
+            record,
+            HandRecord::RevealTokenAccepted { player, card_index: index, .. }
+                if *player == folder.actor_id() && *index == card_index
+        )));
+    }
+
+    // The folded player sends nothing more, the two others open every street.
+    table.check_down(&others);
+    for opened in [3, 4, 5] {
+        table.open_street(&others);
+        assert_eq!(table.state().revealed_cards.len(), opened);
+        table.check_down(&others);
+    }
+    assert_eq!(table.state().phase, Phase::Showdown);
+
+    table.show(others[0]);
+    table.show(others[1]);
+    let state = table.state();
+    assert_eq!(state.phase, Phase::Registration);
+    assert_eq!(state.hand, 1);
+    let shown = state
+        .hand_history(0)
+        .filter(|record| matches!(record, HandRecord::HandShown { .. }))
+        .count();
+    assert_eq!(shown, 2);
+}
+
+#[test]
+fn tokens_are_precommitted_only_by_folded_players_for_closed_cards() {
+    let system = System::new();
+    system.init_logger();
+    let mut table = Table::new(&system, 3, 100);
+    let alice = table.join(1);
+    let bob = table.join(2);
+    let carol = table.join(3);
+    let (folder, others) = deal(&mut table, [&alice, &bob, &carol]);
+    table.send(folder, GameAction::Fold { tokens: vec![] });
+    table.check_down(&others);
+    assert_eq!(table.state().phase, Phase::Flop);
+    let flop = table.state().community_cards[..3].to_vec();
+
+    let share = table.reveal_token_share(others[0], flop[0]);
+    let result = table.send(
+        others[0],
+        GameAction::PrecommitRevealTokens {
+            tokens: vec![share],
+        },
+    );
+    assert!(table.replied(&result, others[0], Err(GameError::StillInHand)));
+
+    let result = table.send(folder, GameAction::PrecommitRevealTokens { tokens: vec![] });
+    assert!(table.replied(&result, folder, Err(GameError::NoRevealTokens)));
+
+    let tokens = flop
+        .iter()
+        .map(|&card_index| table.reveal_token_share(folder, card_index))
+        .collect();
+    let result = table.send(folder, GameAction::PrecommitRevealTokens { tokens });
+    assert!(table.replied(
+        &result,
+        folder,
+        Ok(GameEvent::RevealTokensPrecommitted {
+            player: folder.actor_id(),
+            card_indices: flop.clone(),
+        })
+    ));
+
+    table.open_street(&others);
+    assert_eq!(table.state().revealed_cards.len(), 3);
+
+    let share = table.reveal_token_share(folder, flop[0]);
+    let result = table.send(
+        folder,
+        GameAction::PrecommitRevealTokens {
+            tokens: vec![share],
+        },
+    );
+    assert!(table.replied(&result, folder, Err(GameError::CardAlreadyOpen)));
+}
@@ -0,0 +1,124 @@
+mod common;
+
+use common::*;
+use gtest::System;
+use sc_poker_io::*;
+
+/// Exchanges the hole card tokens and lets the first player to act fold to the big blind.
+/// Returns the id of the winner.
+fn fold_preflop(table: &mut Table, players: &[&Player]) -> u64 {
+    for player in players {
+        table.submit_hole_tokens(player);
+    }
+    let folder = table.to_act(players);
+    let winner = players
+        .iter()
+        .find(|player| player.id != folder.id)
+        .unwrap();
+
+    let result = table.send(folder, GameAction::Fold { tokens: vec![] });
+    let won = GameEvent::HandWon {
+        winner: winner.actor_id(),
+        amount: 3,
+    };
+    assert!(table.replied(&result, folder, Ok(won)));
+    winner.id
+}
+
+#[test]
+fn players_join_and_leave_between_hands() {
+    let system = System::new();
+    system.init_logger();
+    let mut table = Table::new(&system, 3, 100);
+    let alice = table.join(1);
+    let bob = table.join(2);
+    table.send(&alice, GameAction::CloseRegistration);
+    let first_joint_pk = table.state().joint_pk.unwrap();
+
+    // Alice has the button and the small blind, and folds to Bob.
+    table.shuffle_and_deal(&[&alice, &bob]);
+    assert_eq!(fold_preflop(&mut table, &[&alice, &bob]), bob.id);
+
+    let state = table.state();
+    assert_eq!(state.phase, Phase::Registration);
+    assert_eq!(state.hand, 1);
+    assert_eq!(state.button, 1);
+    assert_eq!(state.players[0].stack, BUY_IN - 1);
+    assert_eq!(state.players[1].stack, BUY_IN + 1);
+
+    // Carol takes a seat and Bob leaves with his winnings, the button passes to Carol.
+    let carol = table.join(3);
+    let result = table.send(&bob, GameAction::LeaveTable);
+    let cashed_out = GameEvent::CashedOut {
+        player: bob.actor_id(),
+        amount: BUY_IN + 1 + DEPOSIT,
+    };
+    assert!(table.replied(&result, &bob, Ok(cashed_out)));
+
+    table.send(&carol, GameAction::CloseRegistration);
+    let state = table.state();
+    assert_eq!(state.phase, Phase::Shuffling);
+    assert_eq!(state.button, 1);
+    let seats = state
+        .players
+        .iter()
+        .map(|player| player.id)
+        .collect::<Vec<_>>();
+    assert_eq!(seats, vec![alice.actor_id(), carol.actor_id()]);
+    assert_ne!(state.joint_pk.unwrap(), first_joint_pk);
+
+    // Carol posts the small blind this time and folds to Alice.
+    table.shuffle_and_deal(&[&alice, &carol]);
+    assert_eq!(fold_preflop(&mut table, &[&alice, &carol]), alice.id);
+
+    let state = table.state();
+    assert_eq!(state.phase, Phase::Registration);
+    assert_eq!(state.hand, 2);
+    assert_eq!(state.button, 0);
+    assert_eq!(state.players[0].stack, BUY_IN);
+    assert_eq!(state.players[1].stack, BUY_IN - 1);
+    assert_eq!(state.balances, vec![(bob.actor_id(), BUY_IN + 1 + DEPOSIT)]);
+
+    // Every hand stays in the history, Carol's registration and Bob's departure count
+    // towards the second one.
+    assert!(state.hand_history(0).any(|record| *record
+        == HandRecord::PotCollected {
+            player: bob.actor_id(),
+            amount: 3,
+        }));
+    assert!(state.hand_history(1).any(|record| matches!(
+        record,
+        HandRecord::PlayerJoined { player, .. } if *player == carol.actor_id()
+    )));
+    assert!(state.hand_history(1).any(|record| *record
+        == HandRecord::PlayerLeft {
+            player: bob.actor_id(),
+            amount: BUY_IN + 1 + DEPOSIT,
+        }));
+}
+
+#[test]
+fn button_stays_when_the_hand_ends_before_the_deal() {
+    let system = System::new();
+    system.init_logger();
+    let mut table = Table::new(&system, 3, 100);
+    let alice = table.join(1);
+    let bob = table.join(2);
+    let carol = table.join(3);
+
+    // Bob never shuffles and loses his seat. Nobody posted a blind, so Alice keeps the
+    // button for the next hand.
+    table.shuffle(&alice, true);
+    table.time_out();
+
+    let state = table.state();
+    assert_eq!(state.phase, Phase::Registration);
+    assert_eq!(state.button, 0);
+    let seats = state
+        .players
+        .iter()
+        .map(|player| player.id)
+        .collect::<Vec<_>>();
+    assert_eq!(seats, vec![alice.actor_id(), carol.actor_id()]);
+    assert!(state.balances.contains(&(bob.actor_id(), BUY_IN + DEPOSIT)));
+}
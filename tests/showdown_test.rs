@@ -0,0 +1,161 @@
+mod common;
+
+use common::*;
+use gtest::System;
+use sc_poker_io::*;
+
+/// Plays the hand to the showdown with both players checking every street.
+fn play_to_showdown(table: &mut Table, alice: &Player, bob: &Player) {
+    table.shuffle_and_deal(&[alice, bob]);
+    table.submit_hole_tokens(alice);
+    table.submit_hole_tokens(bob);
+    table.check_down(&[alice, bob]);
+    for _ in 0..3 {
+        table.open_street(&[alice, bob]);
+        table.check_down(&[alice, bob]);
+    }
+    assert_eq!(table.state().phase, Phase::Showdown);
+}
+
+#[test]
+fn showdown_pays_the_best_hand() {
+    let system = System::new();
+    system.init_logger();
+    let mut table = Table::new(&system, 2, 100);
+    let alice = table.join(1);
+    let bob = table.join(2);
+
+    play_to_showdown(&mut table, &alice, &bob);
+    table.show(&alice);
+    table.show(&bob);
+
+    let state = table.state();
+    assert_eq!(state.phase, Phase::Registration);
+    assert_eq!(state.hand, 1);
+    let stacks = state
+        .players
+        .iter()
+        .map(|player| player.stack)
+        .sum::<u128>();
+    assert_eq!(stacks, 2 * BUY_IN);
+    let shown = state
+        .hand_history(0)
+        .filter(|record| matches!(record, HandRecord::HandShown { .. }))
+        .count();
+    assert_eq!(shown, 2);
+}
+
+#[test]
+fn showdown_times_out_for_a_player_who_does_not_show() {
+    let system = System::new();
+    system.init_logger();
+    let mut table = Table::new(&system, 2, 100);
+    let alice = table.join(1);
+    let bob = table.join(2);
+
+    play_to_showdown(&mut table, &alice, &bob);
+    table.show(&alice);
+    table.time_out();
+
+    // Bob forfeits the pot of 4 and his seat, which leaves Alice alone at the table.
+    let state = table.state();
+    assert_eq!(state.phase, Phase::Finished);
+    assert!(state.hand_history(0).any(|record| *record
+        == HandRecord::PlayersTimedOut {
+            players: vec![bob.actor_id()]
+        }));
+    assert!(state
+        .balances
+        .contains(&(alice.actor_id(), BUY_IN + 2 + DEPOSIT)));
+    assert!(state
+        .balances
+        .contains(&(bob.actor_id(), BUY_IN - 2 + DEPOSIT)));
+}
+
+#[test]
+fn invalid_token_at_showdown_is_slashed_and_the_shown_hands_still_ranked() {
+    let system = System::new();
+    system.init_logger();
+    let mut table = Table::new(&system, 2, 100);
+    let alice = table.join(1);
+    let bob = table.join(2);
+
+    play_to_showdown(&mut table, &alice, &bob);
+    table.show(&alice);
+    let state = table.state();
+    let bob_cards = &state
+        .players
+        .iter()
+        .find(|seat| seat.id == bob.actor_id())
+        .unwrap()
+        .cards;
+    let share = table.forged_token_share(&bob, bob_cards[0], bob_cards[1]);
+    let result = table.submit_share(&bob, share);
+    assert!(table.replied(
+        &result,
+        &bob,
+        Ok(GameEvent::PlayerSlashed {
+            player: bob.actor_id(),
+            reason: GameError::InvalidRevealProof,
+            amount: DEPOSIT,
+        })
+    ));
+
+    // Alice's hand is opened and ranked, and she takes the pot of 4 and Bob's deposit.
+    let state = table.state();
+    assert_eq!(state.phase, Phase::Finished);
+    assert!(state.hand_history(0).any(|record| matches!(
+        record,
+        HandRecord::HandShown { player, .. } if *player == alice.actor_id()
+    )));
+    assert!(state.hand_history(0).any(|record| *record
+        == HandRecord::PotCollected {
+            player: alice.actor_id(),
+            amount: 4,
+        }));
+    assert!(state
+        .balances
+        .contains(&(alice.actor_id(), BUY_IN + 2 + 2 * DEPOSIT)));
+    assert!(state.balances.contains(&(bob.actor_id(), BUY_IN - 2)));
+}
+
+#[test]
+fn withholding_tokens_for_the_opponents_hole_cards_forfeits_the_hand() {
+    let system = System::new();
+    system.init_logger();
+    let mut table = Table::new(&system, 2, 100);
+    let alice = table.join(1);
+    let bob = table.join(2);
+
+    table.shuffle_and_deal(&[&alice, &bob]);
+    table.submit_hole_tokens(&alice);
+
+    // Bob keeps the tokens for Alice's cards to himself. His own cards are only opened at
+    // the showdown, and nobody bets before every hole card can be opened.
+    let own_card = table.state().players[1].cards[0];
+    let result = table.submit_token(&bob, own_card);
+    assert!(table.replied(&result, &bob, Err(GameError::OwnCardBeforeShowdown)));
+    let result = table.send(&alice, GameAction::Call);
+    assert!(table.replied(&result, &alice, Err(GameError::WaitingForRevealTokens)));
+    assert_eq!(table.state().turn, None);
+
+    table.time_out();
+
+    // Bob loses his big blind to Alice, who collects the pot of 3 and is left alone.
+    let state = table.state();
+    assert_eq!(state.phase, Phase::Finished);
+    assert!(state
+        .balances
+        .contains(&(alice.actor_id(), BUY_IN + 2 + DEPOSIT)));
+    assert!(state
+        .balances
+        .contains(&(bob.actor_id(), BUY_IN - 2 + DEPOSIT)));
+
+    let amount = BUY_IN + 2 + DEPOSIT;
+    let result = table.send(&alice, GameAction::Withdraw);
+    let withdrawn = GameEvent::Withdrawn {
+        player: alice.actor_id(),
+        amount,
+    };
+    assert!(table.replied(&result, &alice, Ok(withdrawn)));
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use common::*;
+use gtest::System;
+use sc_poker_io::*;
+
+#[test]
+fn invalid_key_ownership_proof_is_slashed() {
+    let system = System::new();
+    system.init_logger();
+    let mut table = Table::new(&system, 2, 100);
+    let alice = table.join(1);
+
+    // Mallory's proof is bound to Alice's address.
+    let mallory = table.join_with_proof_for(3, 1);
+
+    let state = table.state();
+    assert_eq!(state.phase, Phase::Registration);
+    assert_eq!(state.players.len(), 1);
+    assert_eq!(state.players[0].id, alice.actor_id());
+    assert_eq!(state.players[0].stack, BUY_IN + DEPOSIT);
+    assert_eq!(state.slashed, vec![(mallory.actor_id(), DEPOSIT)]);
+    assert_eq!(state.balances, vec![(mallory.actor_id(), BUY_IN)]);
+    assert!(state.hand_history(0).any(|record| *record
+        == HandRecord::PlayerSlashed {
+            player: mallory.actor_id(),
+            reason: GameError::InvalidKeyOwnershipProof,
+            amount: DEPOSIT,
+        }));
+}
+
+#[test]
+fn invalid_shuffle_proof_is_slashed() {
+    let system = System::new();
+    system.init_logger();
+    let mut table = Table::new(&system, 2, 100);
+    let alice = table.join(1);
+    let bob = table.join(2);
+    assert_eq!(table.state().phase, Phase::Shuffling);
+
+    table.shuffle(&alice, false);
+
+    // Alice's deposit goes to Bob, which leaves him alone and ends the session.
+    let state = table.state();
+    assert_eq!(state.phase, Phase::Finished);
+    assert_eq!(state.slashed, vec![(alice.actor_id(), DEPOSIT)]);
+    assert!(state.hand_history(0).any(|record| *record
+        == HandRecord::PlayerSlashed {
+            player: alice.actor_id(),
+            reason: GameError::InvalidShuffleProof,
+            amount: DEPOSIT,
+        }));
+    assert!(state.balances.contains(&(alice.actor_id(), BUY_IN)));
+    assert!(state
+        .balances
+        .contains(&(bob.actor_id(), BUY_IN + 2 * DEPOSIT)));
+}
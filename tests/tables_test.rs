@@ -0,0 +1,59 @@
+mod common;
+
+use common::*;
+use gtest::System;
+use sc_poker_io::*;
+
+#[test]
+fn tables_are_played_apart_and_retired_when_settled() {
+    let system = System::new();
+    system.init_logger();
+    let mut first = Table::new(&system, 2, 100);
+    let mut second = first.create(2, 100);
+    assert_eq!(second.game_id, 1);
+
+    let alice = first.join(1);
+    let bob = second.join(2);
+    let carol = second.join(3);
+    assert_eq!(first.state().phase, Phase::Registration);
+    assert_eq!(first.state().players.len(), 1);
+    assert_eq!(second.state().phase, Phase::Shuffling);
+    assert_eq!(second.state().players.len(), 2);
+
+    // Players of one table are unknown at the other.
+    let result = first.send(&bob, GameAction::LeaveTable);
+    assert!(first.replied(&result, &bob, Err(GameError::NotRegistered)));
+
+    let result = first.program.send(
+        alice.id,
+        GameMessage::Action {
+            game_id: 7,
+            action: GameAction::LeaveTable,
+        },
+    );
+    assert!(first.replied(&result, &alice, Err(GameError::UnknownTable)));
+
+    // A dishonest shuffle ends the session at the second table only.
+    let shuffler = second.seated(&[&bob, &carol])[0];
+    second.shuffle(shuffler, false);
+    assert_eq!(second.state().phase, Phase::Finished);
+    let dave = first.join(4);
+    assert_eq!(first.state().phase, Phase::Shuffling);
+
+    // A finished table is kept until everybody has withdrawn their balance.
+    assert_eq!(second.retire(), Err(GameError::TableInUse));
+    second.send(&bob, GameAction::Withdraw);
+    assert_eq!(second.retire(), Err(GameError::TableInUse));
+    second.send(&carol, GameAction::Withdraw);
+    assert_eq!(second.retire(), Ok(GameEvent::TableRetired { game_id: 1 }));
+    assert_eq!(second.retire(), Err(GameError::UnknownTable));
+
+    // A table in play cannot be retired, and ids are not given out again.
+    assert_eq!(first.retire(), Err(GameError::TableInUse));
+    let state: ProgramState = first.program.read_state().unwrap();
+    assert!(state.table(1).is_none());
+    assert_eq!(first.state().players.len(), 2);
+    assert_eq!(first.state().players[1].id, dave.actor_id());
+    let third = first.create(2, 100);
+    assert_eq!(third.game_id, 2);
+}
@@ -1,4 +1,4 @@
-use ark_ff::{to_bytes, UniformRand};
+use ark_ff::to_bytes;
 use barnett_smart_card_protocol::discrete_log_cards;
 use barnett_smart_card_protocol::BarnettSmartProtocol;
 use proof_essentials::utils::permutation::Permutation;
@@ -23,97 +23,11 @@ type ProofKeyOwnership = schnorr_identification::proof::Proof<Curve>;
 type RemaskingProof = chaum_pedersen_dl_equality::proof::Proof<Curve>;
 type RevealProof = chaum_pedersen_dl_equality::proof::Proof<Curve>;
 use rand::thread_rng;
+use sc_poker_io::encoding::encode_deck;
+use sc_poker_io::ClassicPlayingCard;
 use rand::Rng;
 use std::collections::HashMap;
 
-#[derive(PartialEq, Clone, Copy, Eq)]
-pub enum Suite {
-    Club,
-    Diamond,
-    Heart,
-    Spade,
-}
-
-impl Suite {
-    const VALUES: [Self; 4] = [Self::Club, Self::Diamond, Self::Heart, Self::Spade];
-}
-
-#[derive(PartialEq, PartialOrd, Clone, Copy, Eq)]
-pub enum Value {
-    Two,
-    Three,
-    Four,
-    Five,
-    Six,
-    Seven,
-    Eight,
-    Nine,
-    Ten,
-    Jack,
-    Queen,
-    King,
-    Ace,
-}
-
-impl Value {
-    const VALUES: [Self; 13] = [
-        Self::Two,
-        Self::Three,
-        Self::Four,
-        Self::Five,
-        Self::Six,
-        Self::Seven,
-        Self::Eight,
-        Self::Nine,
-        Self::Ten,
-        Self::Jack,
-        Self::Queen,
-        Self::King,
-        Self::Ace,
-    ];
-}
-
-#[derive(PartialEq, Clone, Eq, Copy)]
-pub struct ClassicPlayingCard {
-    value: Value,
-    suite: Suite,
-}
-
-impl ClassicPlayingCard {
-    pub fn new(value: Value, suite: Suite) -> Self {
-        Self { value, suite }
-    }
-}
-
-impl std::fmt::Debug for ClassicPlayingCard {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let suite = match self.suite {
-            Suite::Club => "♣",
-            Suite::Diamond => "♦",
-            Suite::Heart => "♥",
-            Suite::Spade => "♠",
-        };
-
-        let val = match self.value {
-            Value::Two => "2",
-            Value::Three => "3",
-            Value::Four => "4",
-            Value::Five => "5",
-            Value::Six => "6",
-            Value::Seven => "7",
-            Value::Eight => "8",
-            Value::Nine => "9",
-            Value::Ten => "10",
-            Value::Jack => "J",
-            Value::Queen => "Q",
-            Value::King => "K",
-            Value::Ace => "A",
-        };
-
-        write!(f, "{}{}", val, suite)
-    }
-}
-
 #[derive(Clone)]
 struct Player {
     name: Vec<u8>,
@@ -189,33 +103,24 @@ pub enum GameErrors {
     InvalidCard,
 }
 
-fn encode_cards<R: Rng>(rng: &mut R, num_of_cards: usize) -> HashMap<Card, ClassicPlayingCard> {
-    let mut map: HashMap<Card, ClassicPlayingCard> = HashMap::new();
-    let plaintexts = (0..num_of_cards)
-        .map(|_| Card::rand(rng))
-        .collect::<Vec<_>>();
-
-    let mut i = 0;
-    for value in Value::VALUES.iter().copied() {
-        for suite in Suite::VALUES.iter().copied() {
-            let current_card = ClassicPlayingCard::new(value, suite);
-            map.insert(plaintexts[i], current_card);
-            i += 1;
-        }
-    }
-
-    map
+/// Maps the plaintexts the contract also uses, see `sc_poker_io::encoding`, to the cards
+/// they stand for.
+fn encode_cards() -> HashMap<Card, ClassicPlayingCard> {
+    encode_deck()
+        .into_iter()
+        .enumerate()
+        .map(|(index, card)| (card, ClassicPlayingCard::from_index(index)))
+        .collect()
 }
 
 #[test]
 fn run_game() -> anyhow::Result<()> {
     let m = 2;
     let n = 26;
-    let num_of_cards = m * n;
     let rng = &mut thread_rng();
 
     let parameters = CardProtocol::setup(rng, m, n).unwrap();
-    let card_mapping = encode_cards(rng, num_of_cards);
+    let card_mapping = encode_cards();
 
     let mut alice = Player::new(rng, &parameters, &to_bytes![b"Alice"].unwrap())?;
     let mut bob = Player::new(rng, &parameters, &to_bytes![b"Bob"].unwrap())?;